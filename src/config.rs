@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item, TomlError};
+
+use crate::{
+    generator::Naming,
+    license::License,
+    parser::{Api, GlProfile},
+};
+
+/// The generator settings read from an `angel.toml` file, made of named targets:
+///
+/// ```toml
+/// [targets.gl33]
+/// api = "gl"
+/// version = 3.3
+/// profile = "core"
+/// extensions = ["GL_KHR_debug"]
+/// include = ["glObjectLabel"]
+/// exclude = ["glGetnUniform*"]
+/// registry = "bundled" # "fetch" or the path to a registry xml file
+/// out = "gl"
+/// naming = "stripped" # or "original" to keep the api prefix of the commands
+/// ```
+pub struct Config {
+    pub targets: Vec<Target>,
+}
+
+/// A named set of generator settings, the missing ones use the defaults of the command line.
+#[derive(Default)]
+pub struct Target {
+    pub name: String,
+    pub api: Option<Api>,
+    pub version: Option<f32>,
+    pub profile: Option<GlProfile>,
+    pub extensions: Option<Vec<String>>,
+    pub all_extensions: Option<bool>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    /// "bundled", "fetch" or the path to a registry xml file.
+    pub registry: Option<String>,
+    pub out: Option<PathBuf>,
+    /// The file to write only the bindings module to, "-" for stdout.
+    pub module: Option<PathBuf>,
+    pub split: Option<bool>,
+    pub scan: Option<PathBuf>,
+    pub naming: Option<Naming>,
+    pub crate_name: Option<String>,
+    pub crate_version: Option<String>,
+    pub edition: Option<String>,
+    pub license: Option<License>,
+    pub tracing: Option<bool>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to parse config file")]
+    Toml(#[from] TomlError),
+    #[error("Expected a table of targets")]
+    InvalidTargets,
+    #[error("Expected target {0} to be a table")]
+    InvalidTarget(String),
+    #[error("Unknown key {key} in target {target}")]
+    UnknownKey { target: String, key: String },
+    #[error("Invalid value for {key} in target {target}, expected {expected}")]
+    InvalidValue {
+        target: String,
+        key: String,
+        expected: &'static str,
+    },
+}
+
+impl Config {
+    /// Parses a config file, the relative paths it contains are resolved from the directory it is in.
+    pub fn parse(toml: &str, directory: &Path) -> Result<Self, ConfigError> {
+        let document: Document = toml.parse()?;
+
+        let targets = match document.get("targets") {
+            Some(targets) => targets
+                .as_table_like()
+                .ok_or(ConfigError::InvalidTargets)?
+                .iter()
+                .map(|(name, target)| Target::parse(name, target, directory))
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self { targets })
+    }
+
+    pub fn target(&self, name: &str) -> Option<&Target> {
+        self.targets.iter().find(|target| target.name == name)
+    }
+}
+
+impl Target {
+    fn parse(name: &str, item: &Item, directory: &Path) -> Result<Self, ConfigError> {
+        let table = item
+            .as_table_like()
+            .ok_or_else(|| ConfigError::InvalidTarget(name.to_string()))?;
+
+        let mut target = Target {
+            name: name.to_string(),
+            ..Default::default()
+        };
+
+        for (key, value) in table.iter() {
+            let invalid = |expected| ConfigError::InvalidValue {
+                target: name.to_string(),
+                key: key.to_string(),
+                expected,
+            };
+
+            let string = || value.as_str().ok_or_else(|| invalid("a string"));
+            let strings = || {
+                value
+                    .as_array()
+                    .and_then(|array| {
+                        array
+                            .iter()
+                            .map(|value| value.as_str().map(|s| s.to_string()))
+                            .collect()
+                    })
+                    .ok_or_else(|| invalid("an array of strings"))
+            };
+            let path = || string().map(|path| directory.join(path));
+
+            match key {
+                "api" => {
+                    target.api = Some(
+                        string()?
+                            .parse()
+                            .map_err(|_| invalid("gl, gles1, gles2, glsc2, egl, glx or wgl"))?,
+                    )
+                }
+                // Versions can be written as numbers or strings, e.g. 3.2, 2 or "3.2".
+                "version" => {
+                    let version = match (value.as_float(), value.as_integer(), value.as_str()) {
+                        (Some(version), _, _) => Some(version as f32),
+                        (_, Some(version), _) => Some(version as f32),
+                        (_, _, Some(version)) => version.parse().ok(),
+                        _ => None,
+                    };
+
+                    target.version = Some(version.ok_or_else(|| invalid("a version, e.g. 4.6"))?);
+                }
+                "profile" => {
+                    target.profile = Some(
+                        string()?
+                            .parse()
+                            .map_err(|_| invalid("core, compatibility or common"))?,
+                    )
+                }
+                "extensions" => target.extensions = Some(strings()?),
+                "all-extensions" => {
+                    target.all_extensions =
+                        Some(value.as_bool().ok_or_else(|| invalid("a boolean"))?)
+                }
+                "include" => target.include = Some(strings()?),
+                "exclude" => target.exclude = Some(strings()?),
+                "registry" => {
+                    target.registry = Some(match string()? {
+                        source @ ("bundled" | "fetch") => source.to_string(),
+                        path => directory.join(path).to_string_lossy().into_owned(),
+                    })
+                }
+                "out" => target.out = Some(path()?),
+                "module" => {
+                    target.module = Some(match string()? {
+                        "-" => "-".into(),
+                        module => directory.join(module),
+                    })
+                }
+                "split" => {
+                    target.split = Some(value.as_bool().ok_or_else(|| invalid("a boolean"))?)
+                }
+                "scan" => target.scan = Some(path()?),
+                "naming" => {
+                    target.naming = Some(
+                        string()?
+                            .parse()
+                            .map_err(|_| invalid("stripped or original"))?,
+                    )
+                }
+                "crate-name" => target.crate_name = Some(string()?.to_string()),
+                "crate-version" => target.crate_version = Some(string()?.to_string()),
+                "edition" => target.edition = Some(string()?.to_string()),
+                // The license files are relative to the config file rather than the current directory.
+                "license" => {
+                    let license = string()?;
+                    let license = match license.parse::<License>() {
+                        Ok(license) => license,
+                        Err(_) if directory.join(license).is_file() => {
+                            License::Custom(license.into())
+                        }
+                        Err(_) => {
+                            return Err(invalid(
+                                "none, mit, apache, dual or the path to a license file",
+                            ))
+                        }
+                    };
+                    target.license = Some(license.relative_to(directory));
+                }
+                "tracing" => {
+                    target.tracing = Some(value.as_bool().ok_or_else(|| invalid("a boolean"))?)
+                }
+                key => {
+                    return Err(ConfigError::UnknownKey {
+                        target: name.to_string(),
+                        key: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(target)
+    }
+}
@@ -0,0 +1,326 @@
+use itertools::{EitherOrBoth, Itertools};
+use serde::Serialize;
+use std::{collections::HashMap, fmt};
+
+use crate::parser::{Api, GlCommand, GlParam, GlRegistry};
+
+/// The differences between two registries, or two selections of the same registry.
+#[derive(Serialize)]
+pub struct RegistryDiff<'a> {
+    pub added_commands: Vec<&'a str>,
+    pub removed_commands: Vec<&'a str>,
+    pub changed_commands: Vec<CommandDiff<'a>>,
+    pub added_enums: Vec<EnumKey<'a>>,
+    pub removed_enums: Vec<EnumKey<'a>>,
+    pub changed_enums: Vec<EnumDiff<'a>>,
+    pub added_extensions: Vec<&'a str>,
+    pub removed_extensions: Vec<&'a str>,
+}
+
+/// The name of an enum and the only api using it, some enums have different values depending on the api.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct EnumKey<'a> {
+    pub name: &'a str,
+    pub api: Option<Api>,
+}
+
+/// The changes to the signature of a command present in both registries.
+#[derive(Serialize)]
+pub struct CommandDiff<'a> {
+    pub name: &'a str,
+    /// The old and new return types, if they differ.
+    pub return_type: Option<(&'a str, &'a str)>,
+    pub params: Vec<ParamDiff<'a>>,
+}
+
+/// A parameter added, removed or changed at a given position.
+#[derive(Serialize)]
+pub struct ParamDiff<'a> {
+    pub index: usize,
+    pub old: Option<Param<'a>>,
+    pub new: Option<Param<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Param<'a> {
+    pub name: &'a str,
+    pub gl_type: &'a str,
+}
+
+/// The old and new values of an enum present in both registries.
+#[derive(Serialize)]
+pub struct EnumDiff<'a> {
+    #[serde(flatten)]
+    pub key: EnumKey<'a>,
+    pub old: &'a str,
+    pub new: &'a str,
+}
+
+impl<'a> From<&'a GlParam> for Param<'a> {
+    fn from(gl_param: &'a GlParam) -> Self {
+        Self {
+            name: &gl_param.name,
+            gl_type: &gl_param.gl_type,
+        }
+    }
+}
+
+impl RegistryDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.added_commands.is_empty()
+            && self.removed_commands.is_empty()
+            && self.changed_commands.is_empty()
+            && self.added_enums.is_empty()
+            && self.removed_enums.is_empty()
+            && self.changed_enums.is_empty()
+            && self.added_extensions.is_empty()
+            && self.removed_extensions.is_empty()
+    }
+}
+
+impl GlRegistry {
+    /// Compares the enums, commands and extensions of the registry with a newer one.
+    ///
+    /// Reduce both registries beforehand to compare two selections, e.g. gl 4.5 and 4.6.
+    pub fn diff<'a>(&'a self, new: &'a GlRegistry) -> RegistryDiff<'a> {
+        let old_commands: HashMap<&str, &GlCommand> = self
+            .gl_commands
+            .iter()
+            .map(|gl_command| (gl_command.name.as_str(), gl_command))
+            .collect();
+        let new_commands: HashMap<&str, &GlCommand> = new
+            .gl_commands
+            .iter()
+            .map(|gl_command| (gl_command.name.as_str(), gl_command))
+            .collect();
+
+        let added_commands = new
+            .gl_commands
+            .iter()
+            .map(|gl_command| gl_command.name.as_str())
+            .filter(|name| !old_commands.contains_key(name))
+            .collect();
+        let removed_commands = self
+            .gl_commands
+            .iter()
+            .map(|gl_command| gl_command.name.as_str())
+            .filter(|name| !new_commands.contains_key(name))
+            .collect();
+        let changed_commands = self
+            .gl_commands
+            .iter()
+            .filter_map(|old| diff_command(old, new_commands.get(old.name.as_str())?))
+            .collect();
+
+        let old_enums: HashMap<EnumKey, &str> = self.enum_values().collect();
+        let new_enums: HashMap<EnumKey, &str> = new.enum_values().collect();
+
+        let added_enums = new
+            .enum_values()
+            .map(|(key, _)| key)
+            .filter(|key| !old_enums.contains_key(key))
+            .unique()
+            .collect();
+        let removed_enums = self
+            .enum_values()
+            .map(|(key, _)| key)
+            .filter(|key| !new_enums.contains_key(key))
+            .unique()
+            .collect();
+        let changed_enums = self
+            .enum_values()
+            .filter_map(|(key, old)| {
+                let new = *new_enums.get(&key)?;
+
+                (old != new).then_some(EnumDiff { key, old, new })
+            })
+            .unique_by(|enum_diff| enum_diff.key)
+            .collect();
+
+        let old_extensions: Vec<&str> = self.extension_names().collect();
+        let new_extensions: Vec<&str> = new.extension_names().collect();
+
+        RegistryDiff {
+            added_commands,
+            removed_commands,
+            changed_commands,
+            added_enums,
+            removed_enums,
+            changed_enums,
+            added_extensions: new_extensions
+                .iter()
+                .copied()
+                .filter(|name| !old_extensions.contains(name))
+                .collect(),
+            removed_extensions: old_extensions
+                .iter()
+                .copied()
+                .filter(|name| !new_extensions.contains(name))
+                .collect(),
+        }
+    }
+
+    fn enum_values(&self) -> impl Iterator<Item = (EnumKey<'_>, &str)> {
+        self.gl_enums.iter().map(|gl_enum| {
+            (
+                EnumKey {
+                    name: &gl_enum.name,
+                    api: gl_enum.api,
+                },
+                gl_enum.value.as_str(),
+            )
+        })
+    }
+
+    fn extension_names(&self) -> impl Iterator<Item = &str> {
+        self.gl_extensions
+            .iter()
+            .map(|gl_extension| gl_extension.name.as_str())
+    }
+}
+
+/// Compares the signatures of two versions of a command, parameters are compared by position.
+fn diff_command<'a>(old: &'a GlCommand, new: &'a GlCommand) -> Option<CommandDiff<'a>> {
+    let return_type = (old.return_type != new.return_type)
+        .then(|| (old.rust_return_type(), new.rust_return_type()));
+
+    let params: Vec<ParamDiff> = old
+        .gl_params
+        .iter()
+        .zip_longest(&new.gl_params)
+        .enumerate()
+        .filter_map(|(index, params)| {
+            let (old, new) = match params {
+                EitherOrBoth::Both(old, new) => (Some(old), Some(new)),
+                EitherOrBoth::Left(old) => (Some(old), None),
+                EitherOrBoth::Right(new) => (None, Some(new)),
+            };
+
+            match (old, new) {
+                (Some(old), Some(new)) if old.name == new.name && old.gl_type == new.gl_type => {
+                    None
+                }
+                (old, new) => Some(ParamDiff {
+                    index,
+                    old: old.map(Param::from),
+                    new: new.map(Param::from),
+                }),
+            }
+        })
+        .collect();
+
+    if return_type.is_none() && params.is_empty() {
+        return None;
+    }
+
+    Some(CommandDiff {
+        name: &old.name,
+        return_type,
+        params,
+    })
+}
+
+impl fmt::Display for EnumKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.api {
+            Some(api) => write!(f, "{} ({})", self.name, api.api()),
+            None => f.write_str(self.name),
+        }
+    }
+}
+
+impl fmt::Display for Param<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.gl_type)
+    }
+}
+
+impl fmt::Display for RegistryDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No differences");
+        }
+
+        let mut sections = Vec::new();
+
+        let mut section = |title: &str, lines: Vec<String>| {
+            if !lines.is_empty() {
+                sections.push(format!(
+                    "{title} ({}):\n{}",
+                    lines.len(),
+                    lines.iter().format("\n")
+                ));
+            }
+        };
+
+        let lines = |marker: &str, items: &[&str]| -> Vec<String> {
+            items
+                .iter()
+                .map(|item| format!("  {marker} {item}"))
+                .collect()
+        };
+
+        section("Added commands", lines("+", &self.added_commands));
+        section("Removed commands", lines("-", &self.removed_commands));
+        section(
+            "Changed commands",
+            self.changed_commands
+                .iter()
+                .map(|command_diff| {
+                    let mut lines = vec![format!("  ~ {}", command_diff.name)];
+
+                    if let Some((old, new)) = command_diff.return_type {
+                        lines.push(format!("      return type: {old} -> {new}"));
+                    }
+
+                    for param_diff in &command_diff.params {
+                        lines.push(match (&param_diff.old, &param_diff.new) {
+                            (Some(old), Some(new)) => {
+                                format!("      param {}: {old} -> {new}", param_diff.index)
+                            }
+                            (None, Some(new)) => {
+                                format!("      param {}: added {new}", param_diff.index)
+                            }
+                            (Some(old), None) => {
+                                format!("      param {}: removed {old}", param_diff.index)
+                            }
+                            (None, None) => unreachable!(),
+                        });
+                    }
+
+                    lines.join("\n")
+                })
+                .collect(),
+        );
+        section(
+            "Added enums",
+            self.added_enums
+                .iter()
+                .map(|key| format!("  + {key}"))
+                .collect(),
+        );
+        section(
+            "Removed enums",
+            self.removed_enums
+                .iter()
+                .map(|key| format!("  - {key}"))
+                .collect(),
+        );
+        section(
+            "Changed enums",
+            self.changed_enums
+                .iter()
+                .map(|enum_diff| {
+                    format!(
+                        "  ~ {}: {} -> {}",
+                        enum_diff.key, enum_diff.old, enum_diff.new
+                    )
+                })
+                .collect(),
+        );
+        section("Added extensions", lines("+", &self.added_extensions));
+        section("Removed extensions", lines("-", &self.removed_extensions));
+
+        write!(f, "{}", sections.join("\n\n"))
+    }
+}
@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+use crate::parser::{Api, GlProfile, GlRegistry};
+
+/// The version of the exported json schema, see `docs/export.md`.
+///
+/// Adding fields is not a breaking change, renaming or removing them is and bumps the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The version, profile and extensions the registry was reduced to.
+#[derive(Serialize)]
+pub struct Selection<'a> {
+    pub api: Api,
+    pub version: f32,
+    pub profile: GlProfile,
+    pub extensions: &'a [String],
+    /// The patterns of the enums and commands added to the selection.
+    pub include: &'a [String],
+    /// The patterns of the enums and commands removed from the selection.
+    pub exclude: &'a [String],
+}
+
+/// A registry along with the version of the schema and the selection it was reduced to, if any.
+#[derive(Serialize)]
+pub struct Export<'a> {
+    pub schema_version: u32,
+    pub selection: Option<Selection<'a>>,
+    #[serde(flatten)]
+    pub registry: &'a GlRegistry,
+}
+
+impl<'a> Export<'a> {
+    pub fn new(registry: &'a GlRegistry, selection: Option<Selection<'a>>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            selection,
+            registry,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
@@ -0,0 +1,412 @@
+use glob::{Pattern, PatternError};
+
+use crate::parser::{Api, GlProfile, GlRegistry, GlRequire};
+
+/// Enums and commands to add to or remove from a reduced registry, by exact name or glob pattern, e.g. "glGetnUniform*".
+#[derive(Default)]
+pub struct Filter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid pattern {pattern:?}")]
+pub struct FilterError {
+    pattern: String,
+    #[source]
+    source: PatternError,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the enums and commands matching the pattern, even if the selected version or extensions don't require them.
+    pub fn include(mut self, pattern: &str) -> Result<Self, FilterError> {
+        self.include.push(parse_pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Removes the enums and commands matching the pattern, exclusions take precedence over inclusions.
+    pub fn exclude(mut self, pattern: &str) -> Result<Self, FilterError> {
+        self.exclude.push(parse_pattern(pattern)?);
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn includes(&self, name: &str) -> bool {
+        self.include.iter().any(|pattern| pattern.matches(name))
+    }
+
+    pub fn excludes(&self, name: &str) -> bool {
+        self.exclude.iter().any(|pattern| pattern.matches(name))
+    }
+
+    /// Returns the patterns that don't match any enum or command of the registry, usually a typo.
+    pub fn unknown_patterns(&self, registry: &GlRegistry) -> Vec<&str> {
+        self.include
+            .iter()
+            .chain(&self.exclude)
+            .filter(|pattern| {
+                !registry
+                    .gl_commands
+                    .iter()
+                    .map(|gl_command| &gl_command.name)
+                    .chain(registry.gl_enums.iter().map(|gl_enum| &gl_enum.name))
+                    .any(|name| pattern.matches(name))
+            })
+            .map(|pattern| pattern.as_str())
+            .collect()
+    }
+}
+
+/// Builds the selection a registry is reduced to, see [`GlRegistry::reduce_filtered`].
+///
+/// ```
+/// # use angel_generator::{filter::{Filter, Reduction}, parser::{Api, GlProfile}};
+/// # fn main() -> Result<(), angel_generator::filter::FilterError> {
+/// let reduction = Reduction::new(Api::Gl)
+///     .version(3.3)
+///     .profile(GlProfile::Core)
+///     .extensions(["GL_KHR_debug"])
+///     .filter(Filter::new().include("glObjectLabel")?.exclude("glGetn*")?);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Reduction {
+    api: Api,
+    version: Option<f32>,
+    profile: GlProfile,
+    extensions: Vec<String>,
+    filter: Filter,
+}
+
+impl Reduction {
+    /// Selects the latest version of the api with the core profile, without extensions nor filter.
+    pub fn new(api: Api) -> Self {
+        Self {
+            api,
+            version: None,
+            profile: GlProfile::Core,
+            extensions: Vec::new(),
+            filter: Filter::new(),
+        }
+    }
+
+    pub fn version(mut self, version: f32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn profile(mut self, profile: GlProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Adds extensions to the selection.
+    pub fn extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extensions
+            .extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Reduces the registry, returning the selected version or `None` if the registry doesn't define any version of the api.
+    pub fn reduce(&self, registry: &mut GlRegistry) -> Option<f32> {
+        let version = match self.version {
+            Some(version) => version,
+            None => registry.latest_version(self.api)?,
+        };
+
+        registry.reduce_filtered(
+            self.api,
+            version,
+            self.profile,
+            &self.extensions,
+            &self.filter,
+        );
+
+        Some(version)
+    }
+}
+
+impl GlRegistry {
+    /// Reduces the registry like [`GlRegistry::reduce`], then applies the filter.
+    ///
+    /// The included enums and commands are treated as part of the selected version, so they are enabled by its cargo feature.
+    pub fn reduce_filtered(
+        &mut self,
+        api: Api,
+        version: f32,
+        profile: GlProfile,
+        extensions: &[String],
+        filter: &Filter,
+    ) {
+        // The definitions of the included enums and commands are lost once reduced.
+        let included_commands: Vec<_> = self
+            .gl_commands
+            .iter()
+            .filter(|gl_command| filter.includes(&gl_command.name))
+            .cloned()
+            .collect();
+        let included_enums: Vec<_> = self
+            .gl_enums
+            .iter()
+            .filter(|gl_enum| {
+                filter.includes(&gl_enum.name)
+                    && (gl_enum.api.is_none() || gl_enum.api == Some(api))
+            })
+            .cloned()
+            .collect();
+
+        self.reduce(api, version, profile, extensions);
+
+        let mut gl_require = GlRequire {
+            gl_profile: None,
+            api: None,
+            gl_enums: Vec::new(),
+            gl_commands: Vec::new(),
+            // The filter doesn't know whether the context provides the included commands.
+            optional: true,
+        };
+
+        for gl_command in included_commands {
+            if !self
+                .gl_commands
+                .iter()
+                .any(|required| required.name == gl_command.name)
+            {
+                gl_require.gl_commands.push(gl_command.name.clone());
+                self.gl_commands.push(gl_command);
+            }
+        }
+
+        for gl_enum in included_enums {
+            if !self
+                .gl_enums
+                .iter()
+                .any(|required| required.name == gl_enum.name)
+            {
+                gl_require.gl_enums.push(gl_enum.name.clone());
+                self.gl_enums.push(gl_enum);
+            }
+        }
+
+        let included = !gl_require.gl_enums.is_empty() || !gl_require.gl_commands.is_empty();
+
+        if let Some(gl_feature) = self
+            .gl_features
+            .iter_mut()
+            .max_by(|a, b| a.version.total_cmp(&b.version))
+            .filter(|_| included)
+        {
+            gl_feature.gl_require.push(gl_require);
+        }
+
+        self.retain(|name| !filter.excludes(name));
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Result<Pattern, FilterError> {
+    Pattern::new(pattern).map_err(|source| FilterError {
+        pattern: pattern.to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"<registry>
+        <enums namespace="GL" group="AttribMask" type="bitmask">
+            <enum value="0x00004000" name="GL_COLOR_BUFFER_BIT"/>
+        </enums>
+        <enums namespace="GL">
+            <enum value="0x82E0" name="GL_BUFFER"/>
+        </enums>
+        <commands namespace="GL">
+            <command>
+                <proto>void <name>glClear</name></proto>
+                <param><ptype>GLbitfield</ptype> <name>mask</name></param>
+            </command>
+            <command>
+                <proto>void <name>glGetnUniformfv</name></proto>
+                <param><ptype>GLuint</ptype> <name>program</name></param>
+            </command>
+            <command>
+                <proto>void <name>glObjectLabel</name></proto>
+                <param><ptype>GLenum</ptype> <name>identifier</name></param>
+            </command>
+        </commands>
+        <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+            <require>
+                <enum name="GL_COLOR_BUFFER_BIT"/>
+                <command name="glClear"/>
+                <command name="glGetnUniformfv"/>
+            </require>
+        </feature>
+        <feature api="gl" name="GL_VERSION_4_3" number="4.3">
+            <require>
+                <enum name="GL_BUFFER"/>
+                <command name="glObjectLabel"/>
+            </require>
+        </feature>
+    </registry>"#;
+
+    fn names(registry: &GlRegistry) -> Vec<&str> {
+        registry
+            .gl_commands
+            .iter()
+            .map(|gl_command| gl_command.name.as_str())
+            .chain(
+                registry
+                    .gl_enums
+                    .iter()
+                    .map(|gl_enum| gl_enum.name.as_str()),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn exact_names_and_globs() {
+        let filter = Filter::new()
+            .include("glObjectLabel")
+            .unwrap()
+            .exclude("glGetn*")
+            .unwrap();
+
+        assert!(filter.includes("glObjectLabel"));
+        assert!(!filter.includes("glObjectLabelKHR"));
+        assert!(filter.excludes("glGetnUniformfv"));
+        assert!(!filter.excludes("glGetUniformfv"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(Filter::new().include("glGet[").is_err());
+        assert!(Filter::new().exclude("gl***").is_err());
+    }
+
+    #[test]
+    fn unknown_patterns() {
+        let registry = GlRegistry::parse(REGISTRY).unwrap();
+        let filter = Filter::new()
+            .include("glObjectLabel")
+            .unwrap()
+            .include("glObjetLabel")
+            .unwrap()
+            .exclude("GL_COLOR_*")
+            .unwrap();
+
+        assert_eq!(filter.unknown_patterns(&registry), ["glObjetLabel"]);
+    }
+
+    #[test]
+    fn reduce_filtered() {
+        let mut registry = GlRegistry::parse(REGISTRY).unwrap();
+        let filter = Filter::new()
+            .include("glObjectLabel")
+            .unwrap()
+            .exclude("glGetn*")
+            .unwrap();
+
+        registry.reduce_filtered(Api::Gl, 3.3, GlProfile::Core, &[], &filter);
+
+        assert_eq!(
+            names(&registry),
+            ["glClear", "glObjectLabel", "GL_COLOR_BUFFER_BIT"]
+        );
+
+        // The included commands are required by the selected version, but may be missing from the context.
+        let gl_require = registry
+            .gl_features
+            .iter()
+            .flat_map(|gl_feature| &gl_feature.gl_require)
+            .find(|gl_require| {
+                gl_require
+                    .gl_commands
+                    .contains(&"glObjectLabel".to_string())
+            })
+            .unwrap();
+
+        assert!(gl_require.optional);
+    }
+
+    #[test]
+    fn reduce_filtered_patterns() {
+        let mut registry = GlRegistry::parse(REGISTRY).unwrap();
+        let filter = Filter::new()
+            .include("glObjectLabe?")
+            .unwrap()
+            .include("GL_BUFFE[QR]")
+            .unwrap()
+            .exclude("glGetnUniform[fi]v")
+            .unwrap();
+
+        registry.reduce_filtered(Api::Gl, 3.3, GlProfile::Core, &[], &filter);
+
+        assert_eq!(
+            names(&registry),
+            [
+                "glClear",
+                "glObjectLabel",
+                "GL_COLOR_BUFFER_BIT",
+                "GL_BUFFER"
+            ]
+        );
+    }
+
+    #[test]
+    fn reduction() {
+        let mut registry = GlRegistry::parse(REGISTRY).unwrap();
+        let filter = Filter::new().exclude("glGetn*").unwrap();
+
+        // Without a version the registry is reduced to the latest one.
+        let version = Reduction::new(Api::Gl)
+            .profile(GlProfile::Core)
+            .filter(filter)
+            .reduce(&mut registry);
+
+        assert_eq!(version, Some(4.3));
+        assert_eq!(
+            names(&registry),
+            [
+                "glClear",
+                "glObjectLabel",
+                "GL_COLOR_BUFFER_BIT",
+                "GL_BUFFER"
+            ]
+        );
+        assert_eq!(Reduction::new(Api::Gles2).reduce(&mut registry), None);
+    }
+
+    #[test]
+    fn exclusions_take_precedence() {
+        let mut registry = GlRegistry::parse(REGISTRY).unwrap();
+        let filter = Filter::new()
+            .include("glObjectLabel")
+            .unwrap()
+            .exclude("glObject*")
+            .unwrap();
+
+        registry.reduce_filtered(Api::Gl, 3.3, GlProfile::Core, &[], &filter);
+
+        assert_eq!(
+            names(&registry),
+            ["glClear", "glGetnUniformfv", "GL_COLOR_BUFFER_BIT"]
+        );
+    }
+}
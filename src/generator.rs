@@ -1,6 +1,10 @@
 use itertools::Itertools;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
-use crate::parser::{Api, GlProfile, GlRegistry};
+use crate::parser::{Api, GlCommand, GlEnumKind, GlParam, GlProfile, GlRegistry};
 
 const GL_TYPES: &str = r#"#[cfg(not(feature = "std"))]
 use core::ffi::{c_char, c_double, c_float, c_int, c_short, c_uchar, c_uint, c_ushort, c_void};
@@ -88,246 +92,2790 @@ pub type GLDEBUGPROCAMD = extern "system" fn(
 );
 pub type GLVULKANPROCNV = extern "system" fn();"#;
 
-pub struct Generator;
+const EGL_TYPES: &str = r#"#[cfg(not(feature = "std"))]
+use core::ffi::{c_char, c_int, c_uint, c_void};
 
-impl Generator {
-    // TODO: This function could actually fail, handle that.
-    pub fn generate(registry: &GlRegistry, api: Api, version: f32, profile: GlProfile) -> String {
-        // Iterate through all the gl enums and creates the corresponding rust consts separated by a new line.
-        let formated_enums = &registry.gl_enums.iter().format_with("\n", |gl_enum, f| {
-            // The name and value of the enum can be compied as is.
-            let enum_name = &gl_enum.name;
-            let enum_value = &gl_enum.value;
+#[cfg(feature = "std")]
+use std::os::raw::{c_char, c_int, c_uint, c_void};
 
-            /*
-                The enum types is a bit more special:
-                - If it is a bitmask the type is of "GLbitfield"
-                - If the value equals to "0xFFFFFFFFFFFFFFFF" we need a u64 since "GLenum" is too small
-                - In all other cases "GLenum" should be fine
-                TODO: Check if there are other corner cases.
-            */
-            let enum_type = if gl_enum.bitmask {
-                "GLbitfield"
-            } else if gl_enum.value == "0xFFFFFFFFFFFFFFFF" {
-                "u64"
-            } else {
-                "GLenum"
-            };
+pub type khronos_utime_nanoseconds_t = u64;
+pub type khronos_stime_nanoseconds_t = i64;
+pub type khronos_uint64_t = u64;
+pub type khronos_ssize_t = isize;
+pub type EGLNativeDisplayType = *mut c_void;
+pub type EGLNativePixmapType = *mut c_void;
+pub type EGLNativeWindowType = *mut c_void;
+pub type EGLint = i32;
+pub type NativeDisplayType = EGLNativeDisplayType;
+pub type NativePixmapType = EGLNativePixmapType;
+pub type NativeWindowType = EGLNativeWindowType;
+pub enum AHardwareBuffer {}
+pub type Bool = c_int;
+pub type EGLBoolean = c_uint;
+pub type EGLenum = c_uint;
+pub type EGLAttribKHR = isize;
+pub type EGLAttrib = isize;
+pub type EGLClientBuffer = *mut c_void;
+pub type EGLConfig = *mut c_void;
+pub type EGLContext = *mut c_void;
+pub type EGLDeviceEXT = *mut c_void;
+pub type EGLDisplay = *mut c_void;
+pub type EGLImage = *mut c_void;
+pub type EGLImageKHR = *mut c_void;
+pub type EGLLabelKHR = *mut c_void;
+pub type EGLObjectKHR = *mut c_void;
+pub type EGLOutputLayerEXT = *mut c_void;
+pub type EGLOutputPortEXT = *mut c_void;
+pub type EGLStreamKHR = *mut c_void;
+pub type EGLSurface = *mut c_void;
+pub type EGLSync = *mut c_void;
+pub type EGLSyncKHR = *mut c_void;
+pub type EGLSyncNV = *mut c_void;
+pub type __eglMustCastToProperFunctionPointerType = Option<extern "system" fn()>;
+pub type EGLTimeKHR = khronos_utime_nanoseconds_t;
+pub type EGLTime = khronos_utime_nanoseconds_t;
+pub type EGLTimeNV = khronos_utime_nanoseconds_t;
+pub type EGLuint64NV = khronos_utime_nanoseconds_t;
+pub type EGLuint64KHR = khronos_uint64_t;
+pub type EGLnsecsANDROID = khronos_stime_nanoseconds_t;
+pub type EGLNativeFileDescriptorKHR = c_int;
+pub type EGLsizeiANDROID = khronos_ssize_t;
+pub type EGLSetBlobFuncANDROID = extern "system" fn(
+    key: *const c_void,
+    keySize: EGLsizeiANDROID,
+    value: *const c_void,
+    valueSize: EGLsizeiANDROID,
+);
+pub type EGLGetBlobFuncANDROID = extern "system" fn(
+    key: *const c_void,
+    keySize: EGLsizeiANDROID,
+    value: *mut c_void,
+    valueSize: EGLsizeiANDROID,
+) -> EGLsizeiANDROID;
+#[repr(C)]
+pub struct EGLClientPixmapHI {
+    pub pData: *mut c_void,
+    pub iWidth: EGLint,
+    pub iHeight: EGLint,
+    pub iStride: EGLint,
+}
+pub type EGLDEBUGPROCKHR = extern "system" fn(
+    error: EGLenum,
+    command: *const c_char,
+    messageType: EGLint,
+    threadLabel: EGLLabelKHR,
+    objectLabel: EGLLabelKHR,
+    message: *const c_char,
+);"#;
 
-            f(&format_args!(
-                "pub const {enum_name}: {enum_type} = {enum_value};",
-            ))
-        });
+/// The x11 and glx types, added to the gl ones for glx.
+const GLX_TYPES: &str = r#"#[cfg(not(feature = "std"))]
+use core::ffi::c_ulong;
 
-        // Iterate through all gl commands and format them a fields for the function loader.
-        let formated_fields = &registry
-            .gl_commands
+#[cfg(feature = "std")]
+use std::os::raw::c_ulong;
+
+pub type int32_t = i32;
+pub type int64_t = i64;
+pub type XID = c_ulong;
+pub type Bool = c_int;
+pub type Status = c_int;
+pub type Colormap = XID;
+pub type Font = XID;
+pub type Pixmap = XID;
+pub type Window = XID;
+pub enum Display {}
+pub enum Screen {}
+pub enum XVisualInfo {}
+pub type DMbuffer = *mut c_void;
+pub enum DMparams {}
+pub type VLNode = *mut c_void;
+pub type VLPath = *mut c_void;
+pub type VLServer = *mut c_void;
+pub type GLXFBConfigID = XID;
+pub enum __GLXFBConfigRec {}
+pub type GLXFBConfig = *mut __GLXFBConfigRec;
+pub type GLXContextID = XID;
+pub enum __GLXcontextRec {}
+pub type GLXContext = *mut __GLXcontextRec;
+pub type GLXPixmap = XID;
+pub type GLXDrawable = XID;
+pub type GLXWindow = XID;
+pub type GLXPbuffer = XID;
+pub type __GLXextFuncPtr = Option<extern "system" fn()>;
+pub type GLXVideoCaptureDeviceNV = XID;
+pub type GLXVideoDeviceNV = c_uint;
+pub type GLXVideoSourceSGIX = XID;
+pub type GLXFBConfigIDSGIX = XID;
+pub type GLXFBConfigSGIX = *mut __GLXFBConfigRec;
+pub type GLXPbufferSGIX = XID;
+#[repr(C)]
+pub struct GLXHyperpipeNetworkSGIX {
+    pub pipeName: [c_char; 80],
+    pub networkId: c_int,
+}
+#[repr(C)]
+pub struct GLXHyperpipeConfigSGIX {
+    pub pipeName: [c_char; 80],
+    pub channel: c_int,
+    pub participationType: c_uint,
+    pub timeSlice: c_int,
+}"#;
+
+/// The windows and wgl types, added to the gl ones for wgl.
+const WGL_TYPES: &str = r#"pub type BOOL = c_int;
+pub type CHAR = c_char;
+pub type DWORD = u32;
+pub type FLOAT = c_float;
+pub type INT = c_int;
+pub type INT32 = i32;
+pub type INT64 = i64;
+pub type UINT = c_uint;
+pub type USHORT = c_ushort;
+pub type VOID = c_void;
+pub type LPVOID = *mut c_void;
+pub type LPCSTR = *const c_char;
+pub type COLORREF = DWORD;
+pub type PROC = Option<extern "system" fn()>;
+pub type HANDLE = *mut c_void;
+pub type HDC = HANDLE;
+pub type HGLRC = HANDLE;
+pub type HENHMETAFILE = HANDLE;
+pub enum PIXELFORMATDESCRIPTOR {}
+pub enum LAYERPLANEDESCRIPTOR {}
+pub enum GLYPHMETRICSFLOAT {}
+pub type LPGLYPHMETRICSFLOAT = *mut GLYPHMETRICSFLOAT;
+#[repr(C)]
+pub struct RECT {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+#[repr(C)]
+pub struct _GPU_DEVICE {
+    pub cb: DWORD,
+    pub DeviceName: [CHAR; 32],
+    pub DeviceString: [CHAR; 128],
+    pub Flags: DWORD,
+    pub rcVirtualScreen: RECT,
+}
+pub type GPU_DEVICE = _GPU_DEVICE;
+pub type PGPU_DEVICE = *mut _GPU_DEVICE;
+pub type HPBUFFERARB = HANDLE;
+pub type HPBUFFEREXT = HANDLE;
+pub type HVIDEOOUTPUTDEVICENV = HANDLE;
+pub type HPVIDEODEV = HANDLE;
+pub type HPGPUNV = HANDLE;
+pub type HGPUNV = HANDLE;
+pub type HVIDEOINPUTDEVICENV = HANDLE;"#;
+
+/// The lints allowed for the generated code.
+const LINTS: [&str; 4] = [
+    "bad_style",
+    "clippy::too_many_arguments",
+    "clippy::missing_safety_doc",
+    "clippy::upper_case_acronyms",
+];
+
+/// The cargo features gating the enums and commands of the generated code.
+struct Features<'a> {
+    api: Api,
+    introduced_in: HashMap<&'a str, f32>,
+    required_by: HashMap<&'a str, Vec<&'a str>>,
+    /// The commands of the versions that can be missing from the context.
+    optional: HashSet<&'a str>,
+    /// Whether the versions and extensions are gated at all, a standalone module has no cargo features to do so.
+    gated: bool,
+}
+
+impl<'a> Features<'a> {
+    fn new(registry: &'a GlRegistry, api: Api, profile: GlProfile, output: Output) -> Self {
+        Self {
+            api,
+            introduced_in: registry.introduced_in(api, profile),
+            required_by: registry.required_by(api, profile),
+            optional: registry
+                .gl_features
+                .iter()
+                .filter(|gl_feature| gl_feature.api == api)
+                .flat_map(|gl_feature| &gl_feature.gl_require)
+                .filter(|gl_require| gl_require.optional)
+                .flat_map(|gl_require| &gl_require.gl_commands)
+                .map(|name| name.as_str())
+                .collect(),
+            gated: output == Output::Crate,
+        }
+    }
+
+    /// Returns the cfg predicate enabling an enum or command, it is enabled by the feature of the version introducing it and by the ones of the extensions requiring it.
+    ///
+    /// Returns `None` if the enum or command is not part of the generated code.
+    fn predicate(&self, name: &str) -> Option<String> {
+        let features = self
+            .introduced_in
+            .get(name)
+            .map(|version| self.api.version_feature(*version))
+            .into_iter()
+            .chain(
+                self.required_by
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .map(|extension| extension.to_string()),
+            )
+            .map(|feature| format!(r#"feature = "{feature}""#))
+            .collect::<Vec<String>>();
+
+        match features.as_slice() {
+            [] => None,
+            _ if !self.gated => Some("all()".to_string()),
+            [feature] => Some(feature.clone()),
+            features => Some(format!("any({})", features.join(", "))),
+        }
+    }
+
+    /// Returns the cfg predicate enabling all the enums and commands, or `None` if any of them is not part of the generated code.
+    fn predicate_all(&self, names: &[&str]) -> Option<String> {
+        let predicates = names
             .iter()
-            .format_with(",\n", |gl_command, f| {
-                // The name and the return type of the function can be copied as is.
-                let function_name = &gl_command.name;
-                let function_return_type = &gl_command.return_type;
+            .map(|name| self.predicate(name))
+            .collect::<Option<Vec<String>>>()?;
 
-                // The function parameters are formated by simply joining togheter the gl type separeted by a coma.
-                let function_parameters = &gl_command
-                    .gl_params
-                    .iter()
-                    .format_with(",", |gl_param, f| f(&gl_param.gl_type));
+        Some(format!(
+            "all({})",
+            predicates
+                .iter()
+                .filter(|predicate| *predicate != "all()")
+                .unique()
+                .join(", ")
+        ))
+    }
 
-                // NOTE: This needs to be on two different lines otherwise rustfmt breaks.
+    /// Returns the attribute gating an enum or command.
+    fn cfg(&self, name: &str) -> String {
+        match self.predicate(name) {
+            Some(predicate) if self.gated => format!("#[cfg({predicate})]"),
+            _ => String::new(),
+        }
+    }
+
+    /// Returns the attribute gating an item behind a version or extension feature.
+    fn feature_cfg(&self, feature: &str) -> String {
+        if self.gated {
+            format!(r#"#[cfg(feature = "{feature}")]"#)
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Returns the format string and the arguments printing the parameters of a command, as used by the trace macro.
+fn format_arguments(gl_command: &GlCommand) -> (String, String) {
+    // The trace macro function parameters are all passed the same way with the expection of "GLDEBUGPROC" which has an hardcoded value.
+    // TODO: Check if other parameters need special handling.
+    let parameters = gl_command
+        .gl_params
+        .iter()
+        .format_with(",", |gl_param, f| {
+            if gl_param.gl_type == "GLDEBUGPROC" {
                 f(&format_args!(
-                    r#"{function_name}: extern "system" fn
-                    ({function_parameters}){function_return_type}"#,
+                    "transmute::<_, Option<fn()>>({})",
+                    gl_param.name
                 ))
-            });
+            } else {
+                f(&gl_param.name)
+            }
+        })
+        .to_string();
 
-        // Iterate through all gl commands and format them a fields that can be used as contructors of the function loader.
-        let formated_constructor =
-            &registry
-                .gl_commands
-                .iter()
-                .format_with(",\n", |gl_command, f| {
-                    // The name and the return type of the function can be copied as is.
-                    let command_name = &gl_command.name;
-                    let function_return_type = &gl_command.return_type;
+    // This function tries its best to find which kind of brackets need to be passed to the trace macro.
+    // TODO: Wrote this at like 4am, tired and fueled by monsters. Probably needs refactoring.
+    let brackets = {
+        let mut brackets = String::new();
 
-                    // The function parameters are formated by simply joining togheter the gl type separeted by a coma.
-                    let function_parameters = &gl_command
-                        .gl_params
-                        .iter()
-                        .format_with(",", |gl_param, f| f(&gl_param.gl_type));
+        for gl_param in &gl_command.gl_params {
+            match gl_param.gl_type.as_str() {
+                "GLenum" => brackets.push_str("{:#X}, "),
+                gl_type => {
+                    if gl_type.contains('*') {
+                        brackets.push_str("{:p}, ")
+                    } else {
+                        brackets.push_str("{:?}, ")
+                    }
+                }
+            }
+        }
 
-                    // NOTE: Thise needs to be on multiple lines otherwise rustfmt breaks.
-                    f(&format_args!(
-                        r#"{command_name}: transmute::<*const c_void, 
-                        extern "system" fn({function_parameters}){function_return_type}>
-                        (load_pointer(b"{command_name}\0")?)"#,
-                    ))
-                });
+        brackets.pop();
+        brackets.pop();
 
-        let formated_methods = &registry
-            .gl_commands
-            .iter()
-            .format_with("\n", |gl_command, f| {
-                // The function name is equal to the gl command name with the "gl" prefix stripped.
-                let function_name = gl_command.name.strip_prefix("gl").unwrap(); // TODO: Error handling
-                                                                                 // The function parameters are formated by putting the name and the type next to each other with a colon in between.
-                                                                                 // Each name and type pair is separated by a coma.
-                let function_parameters =
-                    &gl_command.gl_params.iter().format_with(",", |gl_param, f| {
-                        f(&format_args!("{}:{}", gl_param.name, gl_param.gl_type))
-                    });
+        brackets
+    };
 
-                // The return type of the function can be copied as is.
-                let function_return_type = &gl_command.return_type;
+    (brackets, parameters)
+}
 
-                // The inner function refers to the name of the field of the function loader.
-                // The inner function name can be copied as is.
-                let inner_function_name = &gl_command.name;
+/// The buffer bindings the pointers can be offsets into, along with the enum querying the buffer bound.
+const BINDINGS: [(&str, &str); 5] = [
+    ("Array", "GL_ARRAY_BUFFER_BINDING"),
+    ("ElementArray", "GL_ELEMENT_ARRAY_BUFFER_BINDING"),
+    ("DrawIndirect", "GL_DRAW_INDIRECT_BUFFER_BINDING"),
+    ("PixelPack", "GL_PIXEL_PACK_BUFFER_BINDING"),
+    ("PixelUnpack", "GL_PIXEL_UNPACK_BUFFER_BINDING"),
+];
 
-                // The inner function parameters are formated by simply joining togheter the gl type separeted by a coma.
-                let inner_function_parameters = &gl_command
+/// How an argument is captured, pointers are only followed when the size of the data they point to is known.
+enum Captured {
+    /// Numbers and opaque handles are captured as is.
+    Scalar,
+    /// Pointers that can't be followed, along with the binding of the buffer they can be an offset into, if any.
+    Pointer { binding: &'static str, output: bool },
+    /// Function pointers are captured as is but can't be replayed.
+    Function,
+    /// The data pointed to, the size is an expression computing it in bytes.
+    Bytes(String),
+    /// A null terminated string.
+    String,
+    /// An array of strings, the count is an expression computing the number of strings.
+    ///
+    /// The lengths are the parameter holding the length of each string, the strings are null terminated without it.
+    Strings {
+        count: String,
+        lengths: Option<String>,
+    },
+    /// The data written by the command, only its size is captured.
+    Output(String),
+}
+
+impl Captured {
+    fn new(gl_command: &GlCommand, gl_param: &GlParam) -> Self {
+        if gl_param.gl_type.contains("PROC") {
+            return Self::Function;
+        }
+
+        let (pointee, output) = match (
+            gl_param.gl_type.strip_prefix("*const "),
+            gl_param.gl_type.strip_prefix("*mut "),
+        ) {
+            (Some(pointee), _) => (pointee, false),
+            (_, Some(pointee)) => (pointee, true),
+            _ => return Self::Scalar,
+        };
+
+        // Arrays of strings, e.g. the sources of glShaderSource, are the only arrays of pointers that can be followed.
+        if pointee == "*const GLchar" && !output {
+            if let Some(count) = Self::elements(gl_command, gl_param) {
+                let lengths = gl_command
                     .gl_params
                     .iter()
-                    .format_with(",", |gl_param, f| f(&gl_param.name));
+                    .find(|length_param| {
+                        length_param.name == "length"
+                            && length_param.gl_type == "*const GLint"
+                            && length_param.len == gl_param.len
+                    })
+                    .map(|length_param| length_param.name.clone());
 
-                // The trace macro function parameters are all passed the same way with the expection of "GLDEBUGPROC" which has an hardcoded value.
-                // TODO: Check if other parameters need special handling.
-                let trace_parameters =
-                    &gl_command.gl_params.iter().format_with(",", |gl_param, f| {
-                        if gl_param.gl_type == "GLDEBUGPROC" {
-                            f(&format_args!(
-                                "transmute::<_, Option<fn()>>({})",
-                                gl_param.name
-                            ))
-                        } else {
-                            f(&gl_param.name)
-                        }
-                    });
+                return Self::Strings { count, lengths };
+            }
+        }
 
-                // This function tries its best to find which kind of brackets need to be passed to the trace macro.
-                // TODO: Wrote this at like 4am, tired and fueled by monsters. Probably needs refactoring.
-                let brackets = {
-                    let mut brackets = String::new();
-
-                    for gl_param in &gl_command.gl_params {
-                        match gl_param.gl_type.as_str() {
-                            "GLenum" => brackets.push_str("{:#X}, "),
-                            gl_type => {
-                                if gl_type.contains('*') {
-                                    brackets.push_str("{:p}, ")
-                                } else {
-                                    brackets.push_str("{:?}, ")
-                                }
-                            }
-                        }
-                    }
+        if pointee.contains('*') {
+            return Self::Pointer {
+                binding: "None",
+                output,
+            };
+        }
+
+        match (Self::size(gl_command, gl_param, pointee), output) {
+            (Some(size), false) => Self::Bytes(size),
+            (Some(size), true) => Self::Output(size),
+            (None, false) if pointee == "GLchar" && gl_param.len.is_none() => Self::String,
+            (None, _) => Self::Pointer {
+                binding: Self::binding(gl_param, output),
+                output,
+            },
+        }
+    }
 
-                    brackets.pop();
-                    brackets.pop();
+    /// Returns the binding of the buffer a pointer is an offset into when a buffer is bound to it.
+    fn binding(gl_param: &GlParam, output: bool) -> &'static str {
+        match (gl_param.name.as_str(), output) {
+            ("pointer", false) => "Array",
+            ("indices", false) => "ElementArray",
+            ("indirect", false) => "DrawIndirect",
+            ("pixels" | "img", true) => "PixelPack",
+            ("pixels" | "img", false) => "PixelUnpack",
+            _ => "None",
+        }
+    }
 
-                    brackets
-                };
+    /// Returns the expression computing the size in bytes of the data pointed to by a parameter.
+    fn size(gl_command: &GlCommand, gl_param: &GlParam, pointee: &str) -> Option<String> {
+        let elements = Self::elements(gl_command, gl_param)?;
 
-                // NOTE: Thise needs to be on multiple lines otherwise rustfmt breaks.
+        match pointee {
+            "c_void" | "GLvoid" => Some(elements),
+            pointee => Some(format!("({elements}) * core::mem::size_of::<{pointee}>()")),
+        }
+    }
+
+    /// Returns the expression computing the number of elements pointed to by a parameter.
+    ///
+    /// Only lengths multiplying or dividing literals and other parameters are supported, e.g. "count*4" or "bufSize / 4".
+    fn elements(gl_command: &GlCommand, gl_param: &GlParam) -> Option<String> {
+        let len = gl_param.len.as_deref()?;
+        let mut elements = String::new();
+
+        for (index, token) in len.split(['*', '/']).enumerate() {
+            let token = token.trim();
+
+            if index > 0 {
+                let operator = len.match_indices(['*', '/']).nth(index - 1)?.1;
+                elements.push_str(&format!(" {operator} "));
+            }
+
+            if token.parse::<u64>().is_ok() {
+                elements.push_str(token);
+                continue;
+            }
+
+            // The parameters referenced by the length may have been renamed, e.g. "type" becomes "r#type".
+            let length_param = gl_command.gl_params.iter().find(|length_param| {
+                length_param.name == token
+                    || length_param.name == format!("r#{token}")
+                    || length_param.name == format!("{token}_")
+            })?;
+
+            if length_param.gl_type.contains('*') || length_param.gl_type.contains("float") {
+                return None;
+            }
+
+            elements.push_str(&format!("capture::count({})", length_param.name));
+        }
+
+        Some(elements)
+    }
+}
+
+/// Formats a registry version as a `(major, minor)` tuple.
+fn format_version(version: f32) -> String {
+    let version = format!("{version:.1}");
+    let (major, minor) = version.split_once('.').unwrap_or((&version, "0"));
+
+    format!("({major}, {minor})")
+}
+
+pub struct Generator;
+
+/// The pieces of the generated code, laid out in one or several files.
+struct Parts {
+    /// The name of the loader struct, e.g. "Gl".
+    loader: &'static str,
+    /// The header comment and the inner attributes.
+    header: String,
+    /// The imports and the load error, used by every module.
+    prelude: String,
+    types: String,
+    enums: String,
+    /// The loader struct along with the items built around it: extensions, versions, mock, capture...
+    loader_items: String,
+    /// The constructors of the loader.
+    loader_methods: String,
+    /// The methods of the loader calling the commands.
+    commands: String,
+}
+
+/// What the generated code is used as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// The bindings module of the generated crate, the versions and extensions are gated behind its cargo features.
+    Crate,
+    /// A standalone module to drop into another crate or to `include!`, everything selected is always enabled.
+    ///
+    /// Inner attributes can't be included, so the lints are left to the module declaration.
+    Module,
+}
+
+/// How the methods calling the commands are named.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Naming {
+    /// The command name with the api prefix stripped, e.g. `GetString` for "glGetString".
+    #[default]
+    Stripped,
+    /// The command name as is, e.g. `glGetString`.
+    Original,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid naming style")]
+pub struct NamingFromStrError;
+
+impl FromStr for Naming {
+    type Err = NamingFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stripped" => Ok(Self::Stripped),
+            "original" => Ok(Self::Original),
+            _ => Err(NamingFromStrError),
+        }
+    }
+}
+
+impl Naming {
+    /// Returns the name of the method calling a command.
+    fn method_name<'a>(&self, api: Api, command_name: &'a str) -> &'a str {
+        match self {
+            Naming::Stripped => command_name
+                .strip_prefix(api.prefix())
+                .unwrap_or(command_name),
+            Naming::Original => command_name,
+        }
+    }
+}
+
+impl Generator {
+    /// Returns the cargo features of the generated crate.
+    ///
+    /// Each version feature enables the previous one while every extension has its own independent feature.
+    pub fn features(registry: &GlRegistry, api: Api) -> Vec<(String, Vec<String>)> {
+        let versions = registry
+            .gl_features
+            .iter()
+            .filter(|gl_feature| gl_feature.api == api)
+            .map(|gl_feature| gl_feature.version)
+            .sorted_by(f32::total_cmp)
+            .dedup();
+
+        let mut features = Vec::new();
+        let mut previous: Option<String> = None;
+
+        for version in versions {
+            let feature = api.version_feature(version);
+            features.push((feature.clone(), previous.into_iter().collect()));
+            previous = Some(feature);
+        }
+
+        for gl_extension in &registry.gl_extensions {
+            features.push((gl_extension.name.clone(), Vec::new()));
+        }
+
+        features
+    }
+
+    /// Generates the struct holding the extensions supported by the current context, only gl and gles contexts can be queried this way.
+    fn generate_extensions(registry: &GlRegistry, features: &Features) -> String {
+        let formated_fields = registry
+            .gl_extensions
+            .iter()
+            .format_with("\n", |gl_extension, f| {
+                f(&format_args!("pub {}: bool,", gl_extension.name))
+            });
+
+        let formated_arms = registry
+            .gl_extensions
+            .iter()
+            .format_with("\n", |gl_extension, f| {
                 f(&format_args!(
-                    r#"pub unsafe fn {function_name}
-                    (&self,{function_parameters}){function_return_type}
-                    {{
-                        #[cfg(all(debug_assertions, feature = "tracing", feature = "trace-calls"))]
-                        trace!("Calling gl{function_name}({brackets})", {trace_parameters});
-                        (self.{inner_function_name})({inner_function_parameters})
-                    }}"#,
+                    r#"b"{0}" => self.{0} = true,"#,
+                    gl_extension.name
                 ))
             });
 
+        // The extensions can only be queried if the commands and enums needed are part of the generated code, otherwise a stub is used.
+        let indexed_predicate = features.predicate_all(&[
+            "glGetIntegerv",
+            "glGetStringi",
+            "GL_NUM_EXTENSIONS",
+            "GL_EXTENSIONS",
+        ]);
+        let string_predicate = features.predicate_all(&["glGetString", "GL_EXTENSIONS"]);
+
+        // Contexts older than 3.0 raise an error for "GL_NUM_EXTENSIONS", it must not reach the next command checked for errors.
+        let clear_errors = match features.predicate_all(&["glGetError", "GL_NO_ERROR"]) {
+            Some(predicate) => format!(
+                r#"#[cfg({predicate})]
+                unsafe fn clear_errors(gl: &Gl) {{
+                    // Each error flag is reported once, a lost context keeps reporting one so the loop is bounded.
+                    for _ in 0..16 {{
+                        if (gl.glGetError)() == GL_NO_ERROR {{
+                            break;
+                        }}
+                    }}
+                }}
+
+                #[cfg(not({predicate}))]
+                unsafe fn clear_errors(_gl: &Gl) {{}}"#
+            ),
+            None => "unsafe fn clear_errors(_gl: &Gl) {}".to_string(),
+        };
+
+        let load_indexed = match &indexed_predicate {
+            Some(predicate) => format!(
+                r#"#[cfg({predicate})]
+                unsafe fn load_indexed(&mut self, gl: &Gl) -> bool {{
+                    // "glGetStringi" is not loaded when the context is older than the version introducing it, or when it is optional and missing.
+                    if gl.glGetStringi as usize == not_loaded as *const c_void as usize {{
+                        return false;
+                    }}
+
+                    let mut count = 0;
+                    (gl.glGetIntegerv)(GL_NUM_EXTENSIONS, &mut count);
+
+                    for index in 0..count.max(0) as GLuint {{
+                        let name = (gl.glGetStringi)(GL_EXTENSIONS, index);
+
+                        if !name.is_null() {{
+                            self.insert(CStr::from_ptr(name.cast()).to_bytes());
+                        }}
+                    }}
+
+                    count > 0
+                }}
+
+                #[cfg(not({predicate}))]
+                unsafe fn load_indexed(&mut self, _gl: &Gl) -> bool {{
+                    false
+                }}"#
+            ),
+            None => r#"unsafe fn load_indexed(&mut self, _gl: &Gl) -> bool {
+                    false
+                }"#
+            .to_string(),
+        };
+
+        let load_string = match &string_predicate {
+            Some(predicate) => format!(
+                r#"#[cfg({predicate})]
+                unsafe fn load_string(&mut self, gl: &Gl) {{
+                    let names = (gl.glGetString)(GL_EXTENSIONS);
+
+                    if !names.is_null() {{
+                        for name in CStr::from_ptr(names.cast()).to_bytes().split(|c| *c == b' ') {{
+                            self.insert(name);
+                        }}
+                    }}
+                }}
+
+                #[cfg(not({predicate}))]
+                unsafe fn load_string(&mut self, _gl: &Gl) {{}}"#
+            ),
+            None => "unsafe fn load_string(&mut self, _gl: &Gl) {}".to_string(),
+        };
+
+        // The extensions are only marked as supported by the queries above, if any of them is part of the generated code.
+        let insert = match (&indexed_predicate, &string_predicate) {
+            (None, None) => String::new(),
+            (indexed_predicate, string_predicate) => format!(
+                r#"/// Marks an extension as supported, unknown extensions are ignored.
+                #[cfg(any({}))]
+                fn insert(&mut self, name: &[u8]) {{
+                    match name {{
+                        {formated_arms}
+                        _ => {{}}
+                    }}
+                }}"#,
+                indexed_predicate.iter().chain(string_predicate).join(", ")
+            ),
+        };
+
         format!(
             r#"
-/*
-    DO NOT MANUALLY EDIT THIS FILE.
-    EDITING THIS FILES CAN LEAD TO SAFETY BUGS AND MEMORY CORRUPTION.
-*/            
+/// The extensions supported by the current context, regardless of the ones enabled as cargo features.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Extensions {{
+    {formated_fields}
+}}
 
-#![allow(bad_style)]
-#![allow(clippy::too_many_arguments)]
-#![allow(clippy::missing_safety_doc)]
-#![allow(clippy::upper_case_acronyms)]
+impl Extensions {{
+    /// Queries the extensions supported by the current context.
+    ///
+    /// The commands are called directly rather than through the methods checking for errors, since it runs while loading.
+    pub unsafe fn load(gl: &Gl) -> Self {{
+        let mut extensions = Self::default();
 
-#[cfg(not(feature = "std"))]
-use core::{{
-    ffi::{{c_void, CStr}},
-    fmt::Display,
-    mem::transmute,
-}};
-#[cfg(feature = "std")]
-use std::{{ffi::CStr, fmt::Display, mem::transmute, os::raw::c_void}};
+        // Legacy contexts don't support "glGetStringi" and can only list the extensions as a single string.
+        if !extensions.load_indexed(gl) {{
+            extensions.load_string(gl);
+        }}
 
+        Self::clear_errors(gl);
 
-#[cfg(all(feature = "tracing", feature = "trace-calls"))]
-use tracing::{{error, trace}};
+        extensions
+    }}
 
-pub type Result<T, E = LoadError> = core::result::Result<T, E>;
+    {insert}
 
-#[derive(Debug)]
-pub struct LoadError {{
-    pub name: &'static str,
-    pub pointer: usize,
-}}
+    {clear_errors}
 
-#[cfg(feature = "std")]
-impl std::error::Error for LoadError {{}}
+    {load_indexed}
 
-impl Display for LoadError {{
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{
-        write!(
-            f,
-            "Failed to load function \"{{}}\", expected a valid pointer instead got {{}}",
-            self.name, self.pointer
+    {load_string}
+}}"#
         )
-    }}
-}}
+    }
 
-pub use types::*;
-pub mod types {{
-    {GL_TYPES}
+    /// Generates the struct describing the current context along with the loader methods querying it, only gl and gles contexts can be queried this way.
+    fn generate_context_info(features: &Features) -> (String, String) {
+        // The profile mask only exists since gl 3.2, older contexts always behave like compatibility ones.
+        let query_profile = match features.predicate_all(&[
+            "GL_CONTEXT_PROFILE_MASK",
+            "GL_CONTEXT_CORE_PROFILE_BIT",
+            "GL_CONTEXT_COMPATIBILITY_PROFILE_BIT",
+        ]) {
+            Some(predicate) => format!(
+                r#"#[cfg({predicate})]
+                unsafe fn query_profile(get_integerv: extern "system" fn(GLenum, *mut GLint)) -> Option<ContextProfile> {{
+                    let mut mask = 0;
+                    get_integerv(GL_CONTEXT_PROFILE_MASK, &mut mask);
+
+                    if mask & GL_CONTEXT_CORE_PROFILE_BIT as GLint != 0 {{
+                        Some(ContextProfile::Core)
+                    }} else if mask & GL_CONTEXT_COMPATIBILITY_PROFILE_BIT as GLint != 0 {{
+                        Some(ContextProfile::Compatibility)
+                    }} else {{
+                        None
+                    }}
+                }}
+
+                #[cfg(not({predicate}))]
+                unsafe fn query_profile(_get_integerv: extern "system" fn(GLenum, *mut GLint)) -> Option<ContextProfile> {{
+                    None
+                }}"#
+            ),
+            None => r#"unsafe fn query_profile(_get_integerv: extern "system" fn(GLenum, *mut GLint)) -> Option<ContextProfile> {
+                    None
+                }"#
+            .to_string(),
+        };
+
+        // Gl 3.0 and later can also query the version as integers, used when the version string can't be parsed.
+        let query_integers = match features.predicate_all(&["GL_MAJOR_VERSION", "GL_MINOR_VERSION"])
+        {
+            Some(predicate) => format!(
+                r#"#[cfg({predicate})]
+                None => {{
+                    let (mut major, mut minor) = (0, 0);
+                    get_integerv(GL_MAJOR_VERSION, &mut major);
+                    get_integerv(GL_MINOR_VERSION, &mut minor);
+
+                    if major <= 0 {{
+                        return None;
+                    }}
+
+                    (ContextApi::Gl, (major as u32, minor.max(0) as u32))
+                }}
+                #[cfg(not({predicate}))]
+                None => return None,"#
+            ),
+            None => "None => return None,".to_string(),
+        };
+
+        let Some(predicate) =
+            features.predicate_all(&["glGetString", "glGetIntegerv", "GL_VERSION"])
+        else {
+            return (String::new(), String::new());
+        };
+
+        let context_info = format!(
+            r#"
+/// The api implemented by a context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContextApi {{
+    Gl,
+    Gles,
 }}
 
-pub use enums::*;
-pub mod enums {{
-use super::*;
-{formated_enums}
+/// The profile of a desktop gl context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContextProfile {{
+    Core,
+    Compatibility,
 }}
 
-pub struct Gl {{
-{formated_fields}
+/// The api, version and profile of a context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextInfo {{
+    pub api: ContextApi,
+    /// The major and minor version of the context.
+    pub version: (u32, u32),
+    /// The profile of the context, always `None` for gles contexts.
+    pub profile: Option<ContextProfile>,
 }}
 
-impl Gl {{
-    pub unsafe fn load<F>(mut loader_function: F) -> Result<Self>
-    where
-        F: FnMut(&CStr) -> *const c_void,
-    {{
-        let mut load_pointer = |name: &'static [u8]| -> Result<*const c_void> {{
-            let pointer = loader_function(CStr::from_bytes_with_nul_unchecked(name));
-            let pointer_usize = pointer as usize;
-
-            if pointer_usize == core::usize::MAX || pointer_usize < 8 {{
-                Err(LoadError {{
-                    name: core::str::from_utf8_unchecked(&name[..name.len() - 1]),
-                    pointer: pointer_usize,
-                }})
-            }} else {{
-                Ok(pointer)
-            }}
+impl ContextInfo {{
+    /// Parses a version string as returned by "glGetString(GL_VERSION)", e.g. "4.6.0 NVIDIA 535.54" or "OpenGL ES 3.2 Mesa 23.1".
+    pub fn parse(version: &[u8]) -> Option<(ContextApi, (u32, u32))> {{
+        let (api, version) = match version.strip_prefix(b"OpenGL ES") {{
+            Some(version) => (ContextApi::Gles, version),
+            None => (ContextApi::Gl, version),
+        }};
+
+        // Anything before the version number is skipped, e.g. the "-CM" of "OpenGL ES-CM 1.1".
+        let start = version.iter().position(u8::is_ascii_digit)?;
+        let mut numbers = version[start..]
+            .split(|c| !c.is_ascii_digit())
+            .map(|number| core::str::from_utf8(number).ok()?.parse().ok());
+
+        Some((api, (numbers.next()??, numbers.next()??)))
+    }}
+
+    #[cfg({predicate})]
+    unsafe fn query(
+        get_string: extern "system" fn(GLenum) -> *const GLubyte,
+        get_integerv: extern "system" fn(GLenum, *mut GLint),
+    ) -> Option<Self> {{
+        let version = get_string(GL_VERSION);
+
+        if version.is_null() {{
+            return None;
+        }}
+
+        let (api, version) = match Self::parse(CStr::from_ptr(version.cast()).to_bytes()) {{
+            Some(parsed) => parsed,
+            {query_integers}
+        }};
+
+        let profile = match api {{
+            ContextApi::Gles => None,
+            ContextApi::Gl if version < (3, 2) => Some(ContextProfile::Compatibility),
+            ContextApi::Gl => Self::query_profile(get_integerv),
         }};
 
-        Ok(Self {{
-            {formated_constructor}
+        Some(Self {{
+            api,
+            version,
+            profile,
         }})
     }}
 
+    {query_profile}
+}}"#
+        );
+
+        let context_methods = format!(
+            r#"/// Queries the api, version and profile of the current context.
+            #[cfg({predicate})]
+            pub unsafe fn context_info(&self) -> Option<ContextInfo> {{
+                ContextInfo::query(self.glGetString, self.glGetIntegerv)
+            }}
+
+            /// Loads only the commands supported by the version of the current context, the others abort the process when called.
+            ///
+            /// All the commands are loaded if the version can't be detected.
+            #[cfg({predicate})]
+            pub unsafe fn load_for_context<F>(mut loader_function: F) -> Result<Self>
+            where
+                F: FnMut(&CStr) -> *const c_void,
+            {{
+                let get_string = transmute::<*const c_void, extern "system" fn(GLenum) -> *const GLubyte>(
+                    load_pointer(&mut loader_function, b"glGetString\0")?,
+                );
+                let get_integerv = transmute::<*const c_void, extern "system" fn(GLenum, *mut GLint)>(
+                    load_pointer(&mut loader_function, b"glGetIntegerv\0")?,
+                );
+
+                let max_version = ContextInfo::query(get_string, get_integerv).map(|context_info| context_info.version);
+
+                Self::load_inner(loader_function, max_version)
+            }}"#
+        );
+
+        (context_info, context_methods)
+    }
+
+    /// Generates a handle per version exposing only the commands available in it, along with the loader methods creating them.
+    ///
+    /// Each handle dereferences to the one of the previous version, the commands removed by a version are never part of the generated code.
+    fn generate_versions(
+        registry: &GlRegistry,
+        api: Api,
+        features: &Features,
+        naming: Naming,
+    ) -> (String, String) {
+        let versions = registry
+            .gl_features
+            .iter()
+            .filter(|gl_feature| gl_feature.api == api)
+            .map(|gl_feature| gl_feature.version)
+            .sorted_by(f32::total_cmp)
+            .dedup()
+            .collect::<Vec<f32>>();
+
+        // The handles are named after the features of their versions, e.g. "Gl33" or "Gles32".
+        let handle_name = |version: f32| {
+            let feature = api.version_feature(version);
+            let (first, rest) = feature.split_at(1);
+
+            format!("{}{rest}", first.to_uppercase())
+        };
+
+        let formated_handles =
+            versions
+                .iter()
+                .enumerate()
+                .format_with("\n", |(index, version), f| {
+                    let handle = handle_name(*version);
+                    let feature_cfg = features.feature_cfg(&api.version_feature(*version));
+                    let formated_version = format_version(*version);
+
+                    let formated_methods = registry
+                        .gl_commands
+                        .iter()
+                        .filter(|gl_command| {
+                            features.introduced_in.get(gl_command.name.as_str()) == Some(version)
+                        })
+                        .format_with("\n", |gl_command, f| {
+                            let function_name = naming.method_name(api, &gl_command.name);
+                            let function_cfg = features.cfg(&gl_command.name);
+                            let function_return_type = &gl_command.return_type;
+
+                            let function_parameters =
+                                gl_command.gl_params.iter().format_with(",", |gl_param, f| {
+                                    f(&format_args!("{}:{}", gl_param.name, gl_param.gl_type))
+                                });
+
+                            let inner_function_parameters = gl_command
+                                .gl_params
+                                .iter()
+                                .format_with(",", |gl_param, f| f(&gl_param.name));
+
+                            f(&format_args!(
+                                r#"{function_cfg}pub unsafe fn {function_name}
+                        (&self,{function_parameters}){function_return_type}
+                        {{
+                            self.0.{function_name}({inner_function_parameters})
+                        }}"#
+                            ))
+                        });
+
+                    // Every handle but the first one also exposes the commands of the previous versions.
+                    let deref = match index
+                        .checked_sub(1)
+                        .map(|previous| handle_name(versions[previous]))
+                    {
+                        Some(previous) => format!(
+                            r#"
+{feature_cfg}
+impl core::ops::Deref for {handle} {{
+    type Target = {previous};
+
+    fn deref(&self) -> &{previous} {{
+        // Safety: Every handle is a transparent wrapper of the loader.
+        unsafe {{ &*(self as *const Self as *const {previous}) }}
+    }}
+}}"#
+                        ),
+                        None => String::new(),
+                    };
+
+                    f(&format_args!(
+                        r#"
+/// The commands available in version {version:.1}.
+{feature_cfg}
+#[repr(transparent)]
+pub struct {handle}(Gl);
+
+{feature_cfg}
+impl Version for {handle} {{
+    const VERSION: (u32, u32) = {formated_version};
+
+    fn as_gl(&self) -> &Gl {{
+        &self.0
+    }}
+
+    fn into_gl(self) -> Gl {{
+        self.0
+    }}
+
+    unsafe fn from_gl_unchecked(gl: Gl) -> Self {{
+        Self(gl)
+    }}
+}}
+{deref}
+
+{feature_cfg}
+impl {handle} {{
     {formated_methods}
-}}"#,
-        )
+}}"#
+                    ))
+                });
+
+        let versions = format!(
+            r#"
+/// A version of the api, implemented by the handles exposing only the commands available in it.
+pub trait Version: Sized {{
+    /// The major and minor version.
+    const VERSION: (u32, u32);
+
+    /// Returns all the loaded commands, including the ones of later versions and extensions.
+    fn as_gl(&self) -> &Gl;
+
+    /// Unwraps all the loaded commands, including the ones of later versions and extensions.
+    fn into_gl(self) -> Gl;
+
+    /// Wraps the loaded commands without checking whether they include the ones of this version.
+    unsafe fn from_gl_unchecked(gl: Gl) -> Self;
+
+    /// Converts the handle to the one of another version, e.g. after detecting the version of the context.
+    ///
+    /// Fails if the commands of the other version weren't loaded.
+    fn upgrade<V: Version>(self) -> core::result::Result<V, Self> {{
+        self.into_gl()
+            .into_version()
+            .map_err(|gl| unsafe {{ Self::from_gl_unchecked(gl) }})
+    }}
+}}
+{formated_handles}"#
+        );
+
+        let versions_methods = r#"/// Loads the commands up to the version `V`, returning a handle exposing only them.
+            pub unsafe fn load_version<V: Version>(
+                loader_function: impl FnMut(&CStr) -> *const c_void,
+            ) -> Result<V> {
+                Ok(V::from_gl_unchecked(Self::load_up_to(loader_function, V::VERSION)?))
+            }
+
+            /// Wraps the loaded commands in the handle of the version `V`.
+            ///
+            /// Fails if they were loaded up to an older version, e.g. by "load_for_context" on an older context.
+            pub fn into_version<V: Version>(self) -> core::result::Result<V, Self> {
+                match self.max_version {
+                    Some(max_version) if max_version < V::VERSION => Err(self),
+                    _ => Ok(unsafe { V::from_gl_unchecked(self) }),
+                }
+            }"#
+        .to_string();
+
+        (versions, versions_methods)
+    }
+
+    /// Generates the callback forwarding the debug messages of the context to tracing, along with the loader method installing it.
+    fn generate_debug_callback(features: &Features) -> (String, String) {
+        // The core command is preferred over the extension ones, only the variants part of the generated code are tried.
+        let variants = [
+            ("glDebugMessageCallback", None),
+            ("glDebugMessageCallbackKHR", Some("GL_KHR_debug")),
+            ("glDebugMessageCallbackARB", Some("GL_ARB_debug_output")),
+        ]
+        .into_iter()
+        .filter_map(|(command, extension)| Some((command, extension, features.predicate(command)?)))
+        .collect::<Vec<_>>();
+
+        if variants.is_empty() {
+            return (String::new(), String::new());
+        }
+
+        let formated_variants =
+            variants
+                .iter()
+                .format_with("\n", |(command, extension, predicate), f| {
+                    // The extension variants also require the extension to be supported by the context.
+                    let supported = match extension {
+                        Some(extension) => format!(" && self.extensions.{extension}"),
+                        None => String::new(),
+                    };
+
+                    f(&format_args!(
+                        r#"#[cfg({predicate})]
+                if self.{command} as usize != not_loaded as *const c_void as usize{supported} {{
+                    (self.{command})(debug_callback, user_param);
+                    return true;
+                }}"#
+                    ))
+                });
+
+        // Contexts not created with the debug flag only output messages once enabled.
+        let enable_output = match features.predicate_all(&["glEnable", "GL_DEBUG_OUTPUT"]) {
+            Some(predicate) => format!(
+                r#"#[cfg({predicate})]
+                if self.glEnable as usize != not_loaded as *const c_void as usize {{
+                    (self.glEnable)(GL_DEBUG_OUTPUT);
+                }}"#
+            ),
+            None => String::new(),
+        };
+
+        let predicate = if features.gated {
+            format!(
+                r#"all(feature = "trace-messages", any({}))"#,
+                variants
+                    .iter()
+                    .map(|(_, _, predicate)| predicate)
+                    .join(", ")
+            )
+        } else {
+            r#"feature = "trace-messages""#.to_string()
+        };
+
+        let debug_callback = format!(
+            r#"
+/// The severity of a debug message, ordered from the least to the most severe.
+#[cfg({predicate})]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugSeverity {{
+    Notification,
+    Low,
+    Medium,
+    High,
+}}
+
+#[cfg({predicate})]
+impl DebugSeverity {{
+    /// Decodes the severity of a debug message, the values are shared by the core and extension variants.
+    pub fn from_raw(severity: GLenum) -> Option<Self> {{
+        match severity {{
+            0x826B => Some(Self::Notification),
+            0x9148 => Some(Self::Low),
+            0x9147 => Some(Self::Medium),
+            0x9146 => Some(Self::High),
+            _ => None,
+        }}
+    }}
+}}
+
+/// Returns the name of the source of a debug message.
+#[cfg({predicate})]
+pub fn debug_source_name(source: GLenum) -> &'static str {{
+    match source {{
+        0x8246 => "api",
+        0x8247 => "window system",
+        0x8248 => "shader compiler",
+        0x8249 => "third party",
+        0x824A => "application",
+        0x824B => "other",
+        _ => "unknown",
+    }}
+}}
+
+/// Returns the name of the type of a debug message.
+#[cfg({predicate})]
+pub fn debug_type_name(type_: GLenum) -> &'static str {{
+    match type_ {{
+        0x824C => "error",
+        0x824D => "deprecated behavior",
+        0x824E => "undefined behavior",
+        0x824F => "portability",
+        0x8250 => "performance",
+        0x8251 => "other",
+        0x8268 => "marker",
+        0x8269 => "push group",
+        0x826A => "pop group",
+        _ => "unknown",
+    }}
+}}
+
+/// Forwards a debug message to tracing, the minimum severity is passed as the user parameter.
+#[cfg({predicate})]
+extern "system" fn debug_callback(
+    source: GLenum,
+    type_: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    user_param: *mut c_void,
+) {{
+    // Unknown severities are reported as the most severe ones so they are never filtered out.
+    let severity = DebugSeverity::from_raw(severity).unwrap_or(DebugSeverity::High);
+
+    if (severity as usize) < user_param as usize || message.is_null() {{
+        return;
+    }}
+
+    // The length is negative when the message is only null terminated.
+    let message = unsafe {{
+        if length < 0 {{
+            CStr::from_ptr(message).to_bytes()
+        }} else {{
+            core::slice::from_raw_parts(message.cast::<u8>(), length as usize)
+        }}
+    }};
+    let message = core::str::from_utf8(message).unwrap_or("<invalid utf-8>");
+    let source = debug_source_name(source);
+    let kind = debug_type_name(type_);
+
+    match severity {{
+        DebugSeverity::Notification => tracing::debug!(source, kind, id, "{{message}}"),
+        DebugSeverity::Low => tracing::info!(source, kind, id, "{{message}}"),
+        DebugSeverity::Medium => tracing::warn!(source, kind, id, "{{message}}"),
+        DebugSeverity::High => tracing::error!(source, kind, id, "{{message}}"),
+    }}
+}}"#
+        );
+
+        let install_debug_callback = format!(
+            r#"/// Forwards the debug messages of the context with at least the given severity to tracing.
+            ///
+            /// Returns `false` if none of the commands installing the callback were loaded.
+            #[cfg({predicate})]
+            pub unsafe fn install_debug_callback(&self, min_severity: DebugSeverity) -> bool {{
+                let user_param = min_severity as usize as *mut c_void;
+
+                {enable_output}
+
+                {formated_variants}
+
+                false
+            }}"#
+        );
+
+        (debug_callback, install_debug_callback)
+    }
+
+    /// Generates the backend recording the commands called instead of running them, along with the loader function returning its stubs.
+    fn generate_mock(registry: &GlRegistry, features: &Features) -> String {
+        let formated_stubs = registry.gl_commands.iter().format_with("\n", |gl_command, f| {
+            let command_name = &gl_command.name;
+            let command_cfg = features.cfg(command_name);
+            let return_type = &gl_command.return_type;
+            let (brackets, arguments) = format_arguments(gl_command);
+
+            let parameters = gl_command.gl_params.iter().format_with(",", |gl_param, f| {
+                f(&format_args!("{}:{}", gl_param.name, gl_param.gl_type))
+            });
+
+            // Commands return zeroed values by default, function pointers can't be zeroed so they must be set explicitly.
+            let returned = match return_type.strip_prefix("->") {
+                None => String::new(),
+                Some(gl_type) if gl_type.contains("PROC") => format!(
+                    r#"return_value("{command_name}").expect("No return value set for {command_name}")"#
+                ),
+                Some(_) => format!(
+                    r#"return_value("{command_name}").unwrap_or_else(|| core::mem::zeroed())"#
+                ),
+            };
+
+            f(&format_args!(
+                r#"{command_cfg}
+                unsafe extern "system" fn {command_name}({parameters}){return_type} {{
+                    record("{command_name}", format!("{brackets}", {arguments}));
+                    {returned}
+                }}"#
+            ))
+        });
+
+        let formated_arms = registry
+            .gl_commands
+            .iter()
+            .format_with("\n", |gl_command, f| {
+                f(&format_args!(
+                    r#"{}b"{1}" => {1} as *const c_void,"#,
+                    features.cfg(&gl_command.name),
+                    gl_command.name
+                ))
+            });
+
+        format!(
+            r#"
+/// A backend recording the commands called instead of running them, meant to test code without a context.
+#[cfg(feature = "mock")]
+pub mod mock {{
+    use super::*;
+    use std::{{any::Any, cell::RefCell, collections::HashMap}};
+
+    /// A command called through the mock backend.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Call {{
+        pub name: &'static str,
+        /// The arguments formated the same way as when tracing the calls.
+        pub arguments: String,
+    }}
+
+    std::thread_local! {{
+        static CALLS: RefCell<Vec<Call>> = RefCell::new(Vec::new());
+        static RETURN_VALUES: RefCell<HashMap<&'static str, Box<dyn Any>>> = RefCell::new(HashMap::new());
+    }}
+
+    /// Returns the stub of a command, meant to be passed to the loader in place of the one of a context.
+    ///
+    /// Loading queries the extensions supported by the context, the calls should be cleared before asserting them.
+    pub fn loader(name: &CStr) -> *const c_void {{
+        match name.to_bytes() {{
+            {formated_arms}
+            _ => core::ptr::null(),
+        }}
+    }}
+
+    /// Returns the calls recorded on the current thread, clearing them.
+    pub fn take_calls() -> Vec<Call> {{
+        CALLS.with(|calls| calls.take())
+    }}
+
+    /// Clears the calls recorded on the current thread.
+    pub fn clear_calls() {{
+        CALLS.with(|calls| calls.borrow_mut().clear());
+    }}
+
+    /// Sets the value returned by a command called on the current thread, the type must be the exact return type of the command.
+    pub fn set_return_value<T: 'static>(name: &'static str, value: T) {{
+        RETURN_VALUES.with(|return_values| return_values.borrow_mut().insert(name, Box::new(value)));
+    }}
+
+    /// Clears the return values set on the current thread, the commands return zeroed values again.
+    pub fn clear_return_values() {{
+        RETURN_VALUES.with(|return_values| return_values.borrow_mut().clear());
+    }}
+
+    fn record(name: &'static str, arguments: String) {{
+        CALLS.with(|calls| calls.borrow_mut().push(Call {{ name, arguments }}));
+    }}
+
+    #[allow(dead_code)]
+    fn return_value<T: Copy + 'static>(name: &'static str) -> Option<T> {{
+        RETURN_VALUES.with(|return_values| {{
+            return_values
+                .borrow()
+                .get(name)
+                .and_then(|value| value.downcast_ref::<T>())
+                .copied()
+        }})
+    }}
+
+    {formated_stubs}
+}}"#
+        )
+    }
+
+    /// Generates the format capturing the commands called and the replayer issuing them again.
+    fn generate_capture(
+        registry: &GlRegistry,
+        api: Api,
+        features: &Features,
+        naming: Naming,
+    ) -> String {
+        let formated_arms = registry
+            .gl_commands
+            .iter()
+            .format_with("\n", |gl_command, f| {
+                let command_name = &gl_command.name;
+                let command_cfg = features.cfg(command_name);
+
+                let captured = gl_command
+                    .gl_params
+                    .iter()
+                    .map(|gl_param| Captured::new(gl_command, gl_param))
+                    .collect::<Vec<Captured>>();
+
+                // Function pointers captured from another process are meaningless.
+                if captured
+                    .iter()
+                    .any(|captured| matches!(captured, Captured::Function))
+                {
+                    return f(&format_args!(r#"{command_cfg}"{command_name}" => None,"#));
+                }
+
+                let function_name = naming.method_name(api, command_name);
+
+                let bindings =
+                    (0..captured.len()).format_with(", ", |index, f| f(&format_args!("a{index}")));
+
+                let scratches =
+                    captured
+                        .iter()
+                        .enumerate()
+                        .format_with("\n", |(index, captured), f| match captured {
+                            Captured::Output(_) => {
+                                f(&format_args!("let mut o{index} = Vec::new();"))
+                            }
+                            Captured::Strings { .. } => {
+                                f(&format_args!("let mut s{index} = Vec::new();"))
+                            }
+                            // The data written to client memory can't be sized, the query is skipped as it doesn't change the state.
+                            Captured::Pointer { output: true, .. } => f(&format_args!(
+                                "if *a{index} == capture::Value::Unsized {{ return Some(()); }}"
+                            )),
+                            _ => Ok(()),
+                        });
+
+                let arguments =
+                    captured
+                        .iter()
+                        .enumerate()
+                        .format_with(", ", |(index, captured), f| match captured {
+                            Captured::Scalar | Captured::Function => f(&format_args!(
+                                "capture::Scalar::from_bits(a{index}.scalar()?)"
+                            )),
+                            Captured::Pointer { .. } => f(&format_args!("a{index}.offset()? as _")),
+                            Captured::Strings { .. } => {
+                                f(&format_args!("a{index}.strings(&mut s{index})?"))
+                            }
+                            Captured::Bytes(_) | Captured::String => {
+                                f(&format_args!("a{index}.pointer()?.cast()"))
+                            }
+                            Captured::Output(_) => {
+                                f(&format_args!("a{index}.pointer_mut(&mut o{index})?.cast()"))
+                            }
+                        });
+
+                f(&format_args!(
+                    r#"{command_cfg}"{command_name}" => {{
+                    let [{bindings}] = call.arguments.as_slice() else {{
+                        return None;
+                    }};
+                    {scratches}
+                    gl.{function_name}({arguments});
+                    Some(())
+                }}"#
+                ))
+            });
+
+        // The buffer bound is queried when the binding and the command querying it are part of the generated code.
+        let bound_arms = BINDINGS.iter().format_with("\n", |(binding, name), f| {
+            match features.predicate_all(&["glGetIntegerv", name]) {
+                Some(predicate) => f(&format_args!(
+                    r#"#[cfg({predicate})]
+                    Binding::{binding} => {{
+                        let mut buffer = 0;
+                        (self.glGetIntegerv)({name}, &mut buffer);
+                        buffer != 0
+                    }}"#
+                )),
+                None => Ok(()),
+            }
+        });
+
+        format!(
+            r#"
+/// Captures the commands called along with the data they read, so that they can be replayed later.
+///
+/// The capture starts with the "ANGELCAP" magic followed by the version of the format as a little endian `u32`.
+/// Each call is then encoded as the length of its name as a `u16`, the name, the number of arguments as a `u8` and the arguments.
+/// Each argument starts with a tag:
+/// - `0`: a number, an opaque handle or a null pointer, followed by its bits as a `u64`
+/// - `1`: the data read by the command, followed by its length as a `u64` and the data
+/// - `2`: the data written by the command, followed by its length as a `u64`
+/// - `3`: a pointer to data of unknown size read by the command, followed by its address as a `u64`
+/// - `4`: an offset into the buffer bound when the command was called, followed by the offset as a `u64`
+/// - `5`: an array of strings, followed by their number as a `u64` and each string as its length as a `u64` and the string
+/// - `6`: a pointer to data of unknown size written by the command
+///
+/// All the numbers are little endian. The data pointed to is only captured if its size can be computed from the registry,
+/// the other pointers are only replayed when they are null or offsets into a bound buffer. The commands reading data of
+/// unknown size from client memory, e.g. glTexImage2D without a pixel unpack buffer, make the capture fail since they
+/// can't be replayed, while the queries writing data of unknown size are skipped when replaying.
+#[cfg(feature = "capture")]
+pub mod capture {{
+    use super::*;
+    use std::{{
+        io::{{self, Read, Write}},
+        sync::Mutex,
+    }};
+
+    const MAGIC: &[u8; 8] = b"ANGELCAP";
+    const VERSION: u32 = 3;
+
+    /// An argument of a captured call.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum Value {{
+        Scalar(u64),
+        Bytes(Vec<u8>),
+        Output(u64),
+        Pointer(u64),
+        Offset(u64),
+        Strings(Vec<Vec<u8>>),
+        Unsized,
+    }}
+
+    /// The buffer binding a pointer is an offset into when a buffer is bound to it.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Binding {{
+        None,
+        Array,
+        ElementArray,
+        DrawIndirect,
+        PixelPack,
+        PixelUnpack,
+    }}
+
+    impl Value {{
+        /// Captures the data read by a command, null pointers are captured as scalars.
+        pub unsafe fn bytes(pointer: *const u8, size: usize) -> Self {{
+            if pointer.is_null() {{
+                Self::Scalar(0)
+            }} else {{
+                Self::Bytes(core::slice::from_raw_parts(pointer, size).to_vec())
+            }}
+        }}
+
+        /// Captures a null terminated string, including the terminator.
+        pub unsafe fn string(pointer: *const GLchar) -> Self {{
+            if pointer.is_null() {{
+                Self::Scalar(0)
+            }} else {{
+                Self::Bytes(CStr::from_ptr(pointer).to_bytes_with_nul().to_vec())
+            }}
+        }}
+
+        /// Captures an array of strings, each string is either as long as its length or null terminated when there are no lengths
+        /// or its length is negative. The strings are captured with a terminator so that they can be replayed without lengths.
+        pub unsafe fn string_array(pointer: *const *const GLchar, count: usize, lengths: *const GLint) -> Self {{
+            if pointer.is_null() {{
+                return Self::Scalar(0);
+            }}
+
+            let strings = (0..count)
+                .map(|index| {{
+                    let string = *pointer.add(index);
+                    let length = if lengths.is_null() {{ -1 }} else {{ *lengths.add(index) }};
+                    let mut bytes = match usize::try_from(length) {{
+                        Ok(length) => core::slice::from_raw_parts(string.cast::<u8>(), length).to_vec(),
+                        Err(_) => CStr::from_ptr(string).to_bytes().to_vec(),
+                    }};
+                    bytes.push(0);
+                    bytes
+                }})
+                .collect();
+
+            Self::Strings(strings)
+        }}
+
+        /// Captures the size of the data written by a command, null pointers are captured as scalars.
+        pub fn output(pointer: *mut u8, size: usize) -> Self {{
+            if pointer.is_null() {{
+                Self::Scalar(0)
+            }} else {{
+                Self::Output(size as u64)
+            }}
+        }}
+
+        /// Captures a pointer that can't be followed, it is captured as an offset if a buffer is bound to its binding.
+        pub unsafe fn address(gl: &Gl, address: usize, binding: Binding) -> Self {{
+            match address {{
+                0 => Self::Scalar(0),
+                address if gl.buffer_bound(binding) => Self::Offset(address as u64),
+                address => Self::Pointer(address as u64),
+            }}
+        }}
+
+        /// Captures a pointer the command writes data of unknown size to, it is captured as an offset if a buffer is bound to its binding.
+        pub unsafe fn output_address(gl: &Gl, address: usize, binding: Binding) -> Self {{
+            match address {{
+                0 => Self::Scalar(0),
+                address if gl.buffer_bound(binding) => Self::Offset(address as u64),
+                _ => Self::Unsized,
+            }}
+        }}
+
+        pub fn scalar(&self) -> Option<u64> {{
+            match self {{
+                Self::Scalar(bits) => Some(*bits),
+                _ => None,
+            }}
+        }}
+
+        /// Returns a pointer to the captured data.
+        pub fn pointer(&self) -> Option<*const u8> {{
+            match self {{
+                Self::Scalar(0) => Some(core::ptr::null()),
+                Self::Bytes(bytes) => Some(bytes.as_ptr()),
+                _ => None,
+            }}
+        }}
+
+        /// Returns the offset into the bound buffer as a pointer, the addresses of the captured process are never replayed.
+        pub fn offset(&self) -> Option<*const u8> {{
+            match self {{
+                Self::Scalar(0) => Some(core::ptr::null()),
+                Self::Offset(offset) => Some(*offset as usize as *const u8),
+                _ => None,
+            }}
+        }}
+
+        /// Returns a pointer to the captured strings, the scratch holds the pointers to each string.
+        pub fn strings(&self, scratch: &mut Vec<*const GLchar>) -> Option<*const *const GLchar> {{
+            match self {{
+                Self::Scalar(0) => Some(core::ptr::null()),
+                Self::Strings(strings) => {{
+                    *scratch = strings.iter().map(|string| string.as_ptr().cast()).collect();
+                    Some(scratch.as_ptr())
+                }}
+                _ => None,
+            }}
+        }}
+
+        /// Returns a pointer to a zeroed buffer the command can write to.
+        pub fn pointer_mut(&self, scratch: &mut Vec<u8>) -> Option<*mut u8> {{
+            match self {{
+                Self::Scalar(0) => Some(core::ptr::null_mut()),
+                Self::Output(size) => {{
+                    *scratch = vec![0; *size as usize];
+                    Some(scratch.as_mut_ptr())
+                }}
+                _ => None,
+            }}
+        }}
+    }}
+
+    impl Gl {{
+        /// Returns whether a buffer is bound to the binding, the bindings that are not part of the generated code never are.
+        unsafe fn buffer_bound(&self, binding: Binding) -> bool {{
+            match binding {{
+                {bound_arms}
+                _ => false,
+            }}
+        }}
+    }}
+
+    /// The numbers and pointers that are captured as their bits.
+    pub trait Scalar: Copy {{
+        fn to_bits(self) -> u64;
+        fn from_bits(bits: u64) -> Self;
+    }}
+
+    macro_rules! impl_scalar {{
+        ($($scalar:ty),*) => {{
+            $(impl Scalar for $scalar {{
+                fn to_bits(self) -> u64 {{
+                    self as u64
+                }}
+
+                fn from_bits(bits: u64) -> Self {{
+                    bits as Self
+                }}
+            }})*
+        }};
+    }}
+
+    impl_scalar!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+
+    impl Scalar for f32 {{
+        fn to_bits(self) -> u64 {{
+            f32::to_bits(self) as u64
+        }}
+
+        fn from_bits(bits: u64) -> Self {{
+            f32::from_bits(bits as u32)
+        }}
+    }}
+
+    impl Scalar for f64 {{
+        fn to_bits(self) -> u64 {{
+            f64::to_bits(self)
+        }}
+
+        fn from_bits(bits: u64) -> Self {{
+            f64::from_bits(bits)
+        }}
+    }}
+
+    impl<T> Scalar for *const T {{
+        fn to_bits(self) -> u64 {{
+            self as usize as u64
+        }}
+
+        fn from_bits(bits: u64) -> Self {{
+            bits as usize as Self
+        }}
+    }}
+
+    impl<T> Scalar for *mut T {{
+        fn to_bits(self) -> u64 {{
+            self as usize as u64
+        }}
+
+        fn from_bits(bits: u64) -> Self {{
+            bits as usize as Self
+        }}
+    }}
+
+    /// Converts a length parameter to a number of elements, negative lengths are treated as empty.
+    pub fn count<T: TryInto<usize>>(length: T) -> usize {{
+        length.try_into().unwrap_or(0)
+    }}
+
+    /// A captured call.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Call {{
+        pub name: String,
+        pub arguments: Vec<Value>,
+    }}
+
+    /// Writes the calls of a loader as they are made, see `Gl::start_capture`.
+    pub struct Capture {{
+        writer: Mutex<(Box<dyn Write + Send>, Option<io::Error>)>,
+    }}
+
+    impl Capture {{
+        /// Starts a capture by writing the header of the format, the writer should be buffered.
+        pub fn new<W: Write + Send + 'static>(mut writer: W) -> io::Result<Self> {{
+            writer.write_all(MAGIC)?;
+            writer.write_all(&VERSION.to_le_bytes())?;
+
+            Ok(Self {{
+                writer: Mutex::new((Box::new(writer), None)),
+            }})
+        }}
+
+        /// Writes a call, the first error is kept and returned when finishing the capture.
+        ///
+        /// A call reading client memory of unknown size fails the capture, as it couldn't be replayed.
+        pub fn record(&self, name: &str, arguments: &[Value]) {{
+            let mut writer = self.writer.lock().unwrap_or_else(|error| error.into_inner());
+            let (writer, error) = &mut *writer;
+
+            if error.is_none() {{
+                let result = if arguments.iter().any(|argument| matches!(argument, Value::Pointer(_))) {{
+                    Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!("{{name}} reads client memory of unknown size, the capture can't be replayed"),
+                    ))
+                }} else {{
+                    Self::write(writer, name, arguments)
+                }};
+
+                if let Err(record_error) = result {{
+                    *error = Some(record_error);
+                }}
+            }}
+        }}
+
+        fn write(writer: &mut dyn Write, name: &str, arguments: &[Value]) -> io::Result<()> {{
+            writer.write_all(&(name.len() as u16).to_le_bytes())?;
+            writer.write_all(name.as_bytes())?;
+            writer.write_all(&[arguments.len() as u8])?;
+
+            for argument in arguments {{
+                match argument {{
+                    Value::Scalar(bits) => {{
+                        writer.write_all(&[0])?;
+                        writer.write_all(&bits.to_le_bytes())?;
+                    }}
+                    Value::Bytes(bytes) => {{
+                        writer.write_all(&[1])?;
+                        writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+                        writer.write_all(bytes)?;
+                    }}
+                    Value::Output(size) => {{
+                        writer.write_all(&[2])?;
+                        writer.write_all(&size.to_le_bytes())?;
+                    }}
+                    Value::Pointer(address) => {{
+                        writer.write_all(&[3])?;
+                        writer.write_all(&address.to_le_bytes())?;
+                    }}
+                    Value::Offset(offset) => {{
+                        writer.write_all(&[4])?;
+                        writer.write_all(&offset.to_le_bytes())?;
+                    }}
+                    Value::Strings(strings) => {{
+                        writer.write_all(&[5])?;
+                        writer.write_all(&(strings.len() as u64).to_le_bytes())?;
+
+                        for string in strings {{
+                            writer.write_all(&(string.len() as u64).to_le_bytes())?;
+                            writer.write_all(string)?;
+                        }}
+                    }}
+                    Value::Unsized => writer.write_all(&[6])?,
+                }}
+            }}
+
+            Ok(())
+        }}
+
+        /// Flushes the capture, returning the first error that occurred while writing it.
+        pub fn finish(self) -> io::Result<()> {{
+            let (mut writer, error) = self.writer.into_inner().unwrap_or_else(|error| error.into_inner());
+
+            match error {{
+                Some(error) => Err(error),
+                None => writer.flush(),
+            }}
+        }}
+    }}
+
+    /// Reads the calls of a capture.
+    pub struct Reader<R> {{
+        reader: R,
+    }}
+
+    impl<R: Read> Reader<R> {{
+        /// Checks the header of the capture.
+        pub fn new(mut reader: R) -> io::Result<Self> {{
+            let mut magic = [0; 8];
+            reader.read_exact(&mut magic)?;
+
+            if &magic != MAGIC || Self::read_array(&mut reader).map(u32::from_le_bytes)? != VERSION {{
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a capture of a supported version"));
+            }}
+
+            Ok(Self {{ reader }})
+        }}
+
+        /// Reads the next call, returns `None` at the end of the capture.
+        pub fn read_call(&mut self) -> io::Result<Option<Call>> {{
+            let mut length = [0; 2];
+
+            if self.reader.read(&mut length[..1])? == 0 {{
+                return Ok(None);
+            }}
+            self.reader.read_exact(&mut length[1..])?;
+
+            let mut name = vec![0; u16::from_le_bytes(length) as usize];
+            self.reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+            let [count] = Self::read_array(&mut self.reader)?;
+            let mut arguments = Vec::with_capacity(count as usize);
+
+            for _ in 0..count {{
+                let argument = match Self::read_array(&mut self.reader)? {{
+                    [0] => Value::Scalar(u64::from_le_bytes(Self::read_array(&mut self.reader)?)),
+                    [1] => Value::Bytes(self.read_bytes()?),
+                    [2] => Value::Output(u64::from_le_bytes(Self::read_array(&mut self.reader)?)),
+                    [3] => Value::Pointer(u64::from_le_bytes(Self::read_array(&mut self.reader)?)),
+                    [4] => Value::Offset(u64::from_le_bytes(Self::read_array(&mut self.reader)?)),
+                    [5] => {{
+                        let count = u64::from_le_bytes(Self::read_array(&mut self.reader)?);
+                        let strings = (0..count).map(|_| self.read_bytes()).collect::<io::Result<_>>()?;
+                        Value::Strings(strings)
+                    }}
+                    [6] => Value::Unsized,
+                    _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown argument tag")),
+                }};
+
+                arguments.push(argument);
+            }}
+
+            Ok(Some(Call {{ name, arguments }}))
+        }}
+
+        fn read_bytes(&mut self) -> io::Result<Vec<u8>> {{
+            let length = u64::from_le_bytes(Self::read_array(&mut self.reader)?);
+            let mut bytes = Vec::new();
+            (&mut self.reader).take(length).read_to_end(&mut bytes)?;
+
+            if bytes.len() as u64 != length {{
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }}
+
+            Ok(bytes)
+        }}
+
+        fn read_array<const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {{
+            let mut array = [0; N];
+            reader.read_exact(&mut array)?;
+            Ok(array)
+        }}
+    }}
+
+    impl<R: Read> Iterator for Reader<R> {{
+        type Item = io::Result<Call>;
+
+        fn next(&mut self) -> Option<Self::Item> {{
+            self.read_call().transpose()
+        }}
+    }}
+
+    /// Issues a captured call again, returns `false` if the command is unknown, not part of the generated code or the arguments don't match.
+    ///
+    /// Object names are replayed as is, the context should be in the same state as the captured one.
+    pub unsafe fn replay(gl: &Gl, call: &Call) -> bool {{
+        replay_call(gl, call).is_some()
+    }}
+
+    #[allow(unused_variables)]
+    unsafe fn replay_call(gl: &Gl, call: &Call) -> Option<()> {{
+        match call.name.as_str() {{
+            {formated_arms}
+            _ => None,
+        }}
+    }}
+}}"#
+        )
+    }
+
+    /// Generates the table counting the calls and the time spent in each command when profiling.
+    fn generate_profiling(registry: &GlRegistry) -> String {
+        let count = registry.gl_commands.len();
+        let formated_names = registry
+            .gl_commands
+            .iter()
+            .format_with(",\n", |gl_command, f| {
+                f(&format_args!(r#""{}""#, gl_command.name))
+            });
+
+        format!(
+            r#"
+/// Counts the calls and the time spent in each command, see `Gl::stats`.
+#[cfg(feature = "profiling")]
+pub mod profiling {{
+    use std::{{
+        fmt,
+        sync::atomic::{{AtomicU64, Ordering}},
+        time::Duration,
+    }};
+
+    const NAMES: [&str; {count}] = [
+        {formated_names}
+    ];
+
+    /// The number of calls and the time spent in a command.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct CommandStats {{
+        pub name: &'static str,
+        pub calls: u64,
+        pub time: Duration,
+    }}
+
+    impl CommandStats {{
+        /// The average time spent in a call, zero if the command was never called.
+        pub fn average(&self) -> Duration {{
+            match self.calls {{
+                0 => Duration::ZERO,
+                calls => Duration::from_nanos((self.time.as_nanos() / calls as u128) as u64),
+            }}
+        }}
+    }}
+
+    /// The commands called at least once, sorted by the time spent in them.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Stats {{
+        pub commands: Vec<CommandStats>,
+    }}
+
+    impl fmt::Display for Stats {{
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {{
+            writeln!(f, "{{:<48}} {{:>12}} {{:>16}} {{:>12}}", "command", "calls", "total", "average")?;
+
+            for command in &self.commands {{
+                writeln!(
+                    f,
+                    "{{:<48}} {{:>12}} {{:>16?}} {{:>12?}}",
+                    command.name,
+                    command.calls,
+                    command.time,
+                    command.average()
+                )?;
+            }}
+
+            Ok(())
+        }}
+    }}
+
+    /// The counters of every command, updated without locking.
+    pub(crate) struct Counters {{
+        calls: Box<[AtomicU64]>,
+        nanoseconds: Box<[AtomicU64]>,
+    }}
+
+    impl Default for Counters {{
+        fn default() -> Self {{
+            Self {{
+                calls: NAMES.iter().map(|_| AtomicU64::new(0)).collect(),
+                nanoseconds: NAMES.iter().map(|_| AtomicU64::new(0)).collect(),
+            }}
+        }}
+    }}
+
+    impl Counters {{
+        pub(crate) fn record(&self, index: usize, time: Duration) {{
+            self.calls[index].fetch_add(1, Ordering::Relaxed);
+            self.nanoseconds[index].fetch_add(time.as_nanos() as u64, Ordering::Relaxed);
+        }}
+
+        pub(crate) fn stats(&self) -> Stats {{
+            let mut commands = NAMES
+                .iter()
+                .zip(self.calls.iter().zip(self.nanoseconds.iter()))
+                .map(|(name, (calls, nanoseconds))| CommandStats {{
+                    name,
+                    calls: calls.load(Ordering::Relaxed),
+                    time: Duration::from_nanos(nanoseconds.load(Ordering::Relaxed)),
+                }})
+                .filter(|command| command.calls > 0)
+                .collect::<Vec<CommandStats>>();
+
+            commands.sort_by(|a, b| b.time.cmp(&a.time).then(b.calls.cmp(&a.calls)));
+
+            Stats {{ commands }}
+        }}
+
+        pub(crate) fn reset(&self) {{
+            for counter in self.calls.iter().chain(self.nanoseconds.iter()) {{
+                counter.store(0, Ordering::Relaxed);
+            }}
+        }}
+    }}
+
+    #[cfg(test)]
+    mod tests {{
+        use super::*;
+
+        #[test]
+        fn average() {{
+            let stats = |calls| CommandStats {{
+                name: "glClear",
+                calls,
+                time: Duration::from_secs(1 << 33),
+            }};
+
+            assert_eq!(stats(0).average(), Duration::ZERO);
+            assert_eq!(stats(4).average(), Duration::from_secs(1 << 31));
+            // Counts past u32::MAX, the ones truncated to zero included.
+            assert_eq!(stats(1 << 32).average(), Duration::from_secs(2));
+            assert_eq!(stats((1 << 33) + 1).average(), Duration::from_nanos(999_999_999));
+        }}
+    }}
+}}"#
+        )
+    }
+
+    // TODO: This function could actually fail, handle that.
+    pub fn generate(
+        registry: &GlRegistry,
+        api: Api,
+        version: f32,
+        profile: GlProfile,
+        output: Output,
+        naming: Naming,
+    ) -> String {
+        let Parts {
+            loader,
+            header,
+            prelude,
+            types,
+            enums,
+            loader_items,
+            loader_methods,
+            commands,
+        } = Self::generate_parts(registry, api, version, profile, output, naming);
+
+        format!(
+            r#"{header}{prelude}
+
+pub use types::*;
+pub mod types {{
+    {types}
+}}
+
+pub use enums::*;
+pub mod enums {{
+use super::*;
+{enums}
+}}
+
+{loader_items}
+
+impl {loader} {{
+    {loader_methods}
+
+    {commands}
+}}"#
+        )
+    }
+
+    /// Generates the code split into modules, returns the name and content of each file, "mod.rs" declaring the other ones.
+    ///
+    /// The loader, its constructors and the items built around it are in "loader.rs" while the methods calling the commands are in "commands.rs".
+    pub fn generate_split(
+        registry: &GlRegistry,
+        api: Api,
+        version: f32,
+        profile: GlProfile,
+        naming: Naming,
+    ) -> Vec<(&'static str, String)> {
+        let Parts {
+            loader,
+            header,
+            prelude,
+            types,
+            enums,
+            loader_items,
+            loader_methods,
+            commands,
+        } = Self::generate_parts(registry, api, version, profile, Output::Crate, naming);
+
+        vec![
+            (
+                "mod.rs",
+                format!(
+                    r#"{header}{prelude}
+
+pub use types::*;
+pub mod types;
+
+pub use enums::*;
+pub mod enums;
+
+pub use loader::*;
+mod loader;"#
+                ),
+            ),
+            ("types.rs", types),
+            ("enums.rs", format!("use super::*;\n\n{enums}")),
+            (
+                "loader.rs",
+                format!(
+                    r#"use super::*;
+
+// The commands module is a child of this one, so the methods can access the private fields of the loader.
+#[path = "commands.rs"]
+mod commands;
+
+{loader_items}
+
+impl {loader} {{
+    {loader_methods}
+}}"#
+                ),
+            ),
+            (
+                "commands.rs",
+                format!(
+                    r#"use super::*;
+
+impl {loader} {{
+    {commands}
+}}"#
+                ),
+            ),
+        ]
+    }
+
+    fn generate_parts(
+        registry: &GlRegistry,
+        api: Api,
+        version: f32,
+        profile: GlProfile,
+        output: Output,
+        naming: Naming,
+    ) -> Parts {
+        // The types used by the registry, egl defines its own while glx and wgl extend the gl ones with the platform types.
+        // The platform types are opaque, they are meant to be casted from and to the ones of the crate used for windowing.
+        let (types, enum_type, signed_type, bitmask_type) = match api {
+            Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => {
+                (GL_TYPES.to_string(), "GLenum", "GLint", "GLbitfield")
+            }
+            Api::Egl => (EGL_TYPES.to_string(), "EGLint", "EGLint", "EGLint"),
+            Api::Glx => (
+                format!("{GL_TYPES}\n{GLX_TYPES}"),
+                "GLenum",
+                "GLint",
+                "GLbitfield",
+            ),
+            Api::Wgl => (
+                format!("{GL_TYPES}\n{WGL_TYPES}"),
+                "GLenum",
+                "GLint",
+                "GLbitfield",
+            ),
+        };
+
+        let features = Features::new(registry, api, profile, output);
+        let cfg = |name: &str| features.cfg(name);
+
+        // Iterate through all the gl enums and creates the corresponding rust consts separated by a new line.
+        let formated_enums = &registry.gl_enums.iter().format_with("\n", |gl_enum, f| {
+            // The name and value of the enum can be compied as is.
+            let enum_name = &gl_enum.name;
+            let enum_value = &gl_enum.value;
+            let enum_cfg = cfg(enum_name);
+
+            /*
+                The enum types is a bit more special:
+                - If the value is explicitly casted the type is the one of the cast
+                - Otherwise the type depends on the kind of the value, the api decides the actual types, e.g. "GLenum" and "GLbitfield" for gl
+            */
+            if let Some(cast_type) = &gl_enum.gl_type {
+                return f(&format_args!(
+                    "{enum_cfg}pub const {enum_name}: {cast_type} = {enum_value} as {cast_type};",
+                ));
+            }
+
+            let enum_type = match gl_enum.kind {
+                GlEnumKind::Unsigned => enum_type,
+                GlEnumKind::Unsigned64 => "u64",
+                GlEnumKind::Signed => signed_type,
+                GlEnumKind::Bitmask => bitmask_type,
+                GlEnumKind::String => "&str",
+            };
+
+            f(&format_args!(
+                "{enum_cfg}pub const {enum_name}: {enum_type} = {enum_value};",
+            ))
+        });
+
+        // Iterate through all gl commands and format them a fields for the function loader.
+        let formated_fields = &registry
+            .gl_commands
+            .iter()
+            .format_with(",\n", |gl_command, f| {
+                // The name and the return type of the function can be copied as is.
+                let function_name = &gl_command.name;
+                let function_return_type = &gl_command.return_type;
+                let function_cfg = cfg(function_name);
+
+                // The function parameters are formated by simply joining togheter the gl type separeted by a coma.
+                let function_parameters = &gl_command
+                    .gl_params
+                    .iter()
+                    .format_with(",", |gl_param, f| f(&gl_param.gl_type));
+
+                // NOTE: This needs to be on two different lines otherwise rustfmt breaks.
+                f(&format_args!(
+                    r#"{function_cfg}{function_name}: extern "system" fn
+                    ({function_parameters}){function_return_type}"#,
+                ))
+            });
+
+        // Iterate through all gl commands and format them a fields that can be used as contructors of the function loader.
+        let formated_constructor =
+            &registry
+                .gl_commands
+                .iter()
+                .format_with(",\n", |gl_command, f| {
+                    // The name and the return type of the function can be copied as is.
+                    let command_name = &gl_command.name;
+                    let function_return_type = &gl_command.return_type;
+                    let command_cfg = cfg(command_name);
+
+                    // The function parameters are formated by simply joining togheter the gl type separeted by a coma.
+                    let function_parameters = &gl_command
+                        .gl_params
+                        .iter()
+                        .format_with(",", |gl_param, f| f(&gl_param.gl_type));
+
+                    // Commands introduced by a version can be skipped when loading from an older context.
+                    // The ones also required by an enabled extension are always loaded since the extension can provide them on older contexts.
+                    // Only the commands of the versions are mandatory, the ones of the extensions fall back to the "not_loaded" stub when missing.
+                    let (command_version, command_optional) =
+                        match (
+                            features.introduced_in.get(command_name.as_str()),
+                            features.required_by.get(command_name.as_str()),
+                        ) {
+                            // Without features the extensions are always enabled.
+                            (Some(_), Some(_)) if !features.gated => ("None".to_string(), "true".to_string()),
+                            (Some(version), Some(extensions)) => {
+                                let enabled = format!(
+                                    "cfg!(any({}))",
+                                    extensions.iter().format_with(", ", |extension, f| f(
+                                        &format_args!(r#"feature = "{extension}""#)
+                                    ))
+                                );
+
+                                (
+                                    format!("if {enabled} {{ None }} else {{ Some({}) }}", format_version(*version)),
+                                    enabled,
+                                )
+                            }
+                            (Some(version), None) => (
+                                format!("Some({})", format_version(*version)),
+                                features.optional.contains(command_name.as_str()).to_string(),
+                            ),
+                            (None, _) => ("None".to_string(), "true".to_string()),
+                        };
+
+                    // NOTE: Thise needs to be on multiple lines otherwise rustfmt breaks.
+                    f(&format_args!(
+                        r#"{command_cfg}{command_name}: transmute::<*const c_void,
+                        extern "system" fn({function_parameters}){function_return_type}>
+                        (load_pointer(b"{command_name}\0", {command_version}, {command_optional})?)"#,
+                    ))
+                });
+
+        // Only gl and gles commands can be checked for errors, as long as "glGetError" is part of the generated code.
+        let error_predicate = match api {
+            Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => {
+                features.predicate_all(&["glGetError", "GL_NO_ERROR"])
+            }
+            _ => None,
+        };
+        let check_cfg = match &error_predicate {
+            Some(predicate) if features.gated => {
+                format!(r#"all(debug_assertions, feature = "check-errors", {predicate})"#)
+            }
+            Some(_) => r#"all(debug_assertions, feature = "check-errors")"#.to_string(),
+            None => String::new(),
+        };
+
+        let formated_methods = &registry
+            .gl_commands
+            .iter()
+            .enumerate()
+            .format_with("\n", |(command_index, gl_command), f| {
+                // The function name is equal to the gl command name, with the api prefix, e.g. "gl" or "egl", stripped by default.
+                let function_name = naming.method_name(api, &gl_command.name);
+
+                // The function parameters are formated by putting the name and the type next to each other with a colon in between.
+                // Each name and type pair is separated by a coma.
+                let function_parameters =
+                    &gl_command.gl_params.iter().format_with(",", |gl_param, f| {
+                        f(&format_args!("{}:{}", gl_param.name, gl_param.gl_type))
+                    });
+
+                // The return type of the function can be copied as is.
+                let function_return_type = &gl_command.return_type;
+
+                // The inner function refers to the name of the field of the function loader.
+                // The inner function name can be copied as is.
+                let inner_function_name = &gl_command.name;
+                let function_cfg = cfg(inner_function_name);
+
+                // The inner function parameters are formated by simply joining togheter the gl type separeted by a coma.
+                let inner_function_parameters = &gl_command
+                    .gl_params
+                    .iter()
+                    .format_with(",", |gl_param, f| f(&gl_param.name));
+
+                // NOTE: This is used by both the trace macro and the error check so it can't be lazily formated.
+                let (brackets, trace_parameters) = format_arguments(gl_command);
+
+                // The errors are checked after every command but "glGetError" itself, commands between "glBegin" and "glEnd" can't check them.
+                let call = match api {
+                    // Gl and gles commands are timed when profiling, the index refers to the table of counters.
+                    Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => format!(
+                        "self.timed({command_index}, || (self.{inner_function_name})({inner_function_parameters}))"
+                    ),
+                    _ => format!("(self.{inner_function_name})({inner_function_parameters})"),
+                };
+                let check = format!(
+                    r#"#[cfg({check_cfg})]
+                    self.check_error("{inner_function_name}", format_args!("{brackets}", {trace_parameters}));"#
+                );
+                let body = match (inner_function_name.as_str(), &error_predicate) {
+                    (_, None) | ("glGetError", _) => call,
+                    ("glBegin", _) => format!(
+                        r#"{call};
+                        #[cfg({check_cfg})]
+                        self.in_begin_end.store(true, Ordering::Relaxed);"#
+                    ),
+                    ("glEnd", _) => format!(
+                        r#"{call};
+                        #[cfg({check_cfg})]
+                        self.in_begin_end.store(false, Ordering::Relaxed);
+                        {check}"#
+                    ),
+                    _ if function_return_type.is_empty() => format!("{call};\n{check}"),
+                    _ => format!("let result = {call};\n{check}\nresult"),
+                };
+
+                // Gl and gles commands can be captured along with the data they read, before they are called.
+                let capture = match api {
+                    Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => {
+                        let values = gl_command.gl_params.iter().format_with(", ", |gl_param, f| {
+                            let name = &gl_param.name;
+
+                            match Captured::new(gl_command, gl_param) {
+                                Captured::Scalar => f(&format_args!("capture::Value::Scalar(capture::Scalar::to_bits({name}))")),
+                                Captured::Pointer { binding, output: false } => f(&format_args!("capture::Value::address(self, {name} as usize, capture::Binding::{binding})")),
+                                Captured::Pointer { binding, output: true } => f(&format_args!("capture::Value::output_address(self, {name} as usize, capture::Binding::{binding})")),
+                                Captured::Strings { count, lengths } => f(&format_args!(
+                                    "capture::Value::string_array({name}, {count}, {})",
+                                    lengths.as_deref().unwrap_or("core::ptr::null()")
+                                )),
+                                Captured::Function => f(&format_args!("capture::Value::Scalar({name} as usize as u64)")),
+                                Captured::Bytes(size) => f(&format_args!("capture::Value::bytes({name}.cast(), {size})")),
+                                Captured::String => f(&format_args!("capture::Value::string({name})")),
+                                Captured::Output(size) => f(&format_args!("capture::Value::output({name}.cast(), {size})")),
+                            }
+                        });
+
+                        format!(
+                            r#"#[cfg(feature = "capture")]
+                            if let Some(capture) = &self.capture {{
+                                capture.record("{inner_function_name}", &[{values}]);
+                            }}"#
+                        )
+                    }
+                    _ => String::new(),
+                };
+
+                // NOTE: Thise needs to be on multiple lines otherwise rustfmt breaks.
+                f(&format_args!(
+                    r#"{function_cfg}pub unsafe fn {function_name}
+                    (&self,{function_parameters}){function_return_type}
+                    {{
+                        #[cfg(all(debug_assertions, feature = "trace-calls"))]
+                        trace!("Calling {inner_function_name}({brackets})", {trace_parameters});
+                        {capture}
+                        {body}
+                    }}"#,
+                ))
+            });
+
+        let loader = api.loader();
+
+        // Gl and gles loaders also detect the extensions supported by the context once loaded and can query its version.
+        let (extensions, extensions_field, extensions_methods, load_body) = match api {
+            Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => {
+                let (context_info, context_methods) = Self::generate_context_info(&features);
+                let (versions, versions_methods) =
+                    Self::generate_versions(registry, api, &features, naming);
+                let (debug_callback, debug_callback_method) =
+                    Self::generate_debug_callback(&features);
+                let mock = Self::generate_mock(registry, &features);
+                let capture = Self::generate_capture(registry, api, &features, naming);
+                let profiling = Self::generate_profiling(registry);
+
+                // The errors can only be checked if "glGetError" is part of the generated code.
+                let check_error = match &error_predicate {
+                    Some(_) => format!(
+                        r#"#[cfg({check_cfg})]
+                        fn check_error(&self, command: &'static str, arguments: fmt::Arguments<'_>) {{
+                            if self.in_begin_end.load(Ordering::Relaxed) {{
+                                return;
+                            }}
+
+                            let error = (self.glGetError)();
+
+                            if error == GL_NO_ERROR {{
+                                return;
+                            }}
+
+                            match self.error_hook {{
+                                Some(hook) => hook(command, arguments, error),
+                                #[cfg(feature = "trace-errors")]
+                                None => error!("{{command}}({{arguments}}) generated error {{error:#X}}"),
+                                #[cfg(not(feature = "trace-errors"))]
+                                None => panic!("{{command}}({{arguments}}) generated error {{error:#X}}"),
+                            }}
+                        }}"#
+                    ),
+                    None => String::new(),
+                };
+
+                // The state used to check the errors only exists when they are actually checked, e.g. not in release builds.
+                let (check_imports, check_field, check_init) = match &error_predicate {
+                    Some(_) => (
+                        format!(
+                            r#"#[cfg({check_cfg})]
+                            use core::sync::atomic::{{AtomicBool, Ordering}};
+                            #[cfg(all({check_cfg}, feature = "trace-errors"))]
+                            use tracing::error;"#
+                        ),
+                        format!(
+                            r#"
+                            #[cfg({check_cfg})]
+                            in_begin_end: AtomicBool,"#
+                        ),
+                        format!(
+                            r#"
+                            #[cfg({check_cfg})]
+                            in_begin_end: AtomicBool::new(false),"#
+                        ),
+                    ),
+                    None => (String::new(), String::new(), String::new()),
+                };
+
+                (
+                    format!(
+                        r#"{}
+{context_info}
+{versions}
+{debug_callback}
+{mock}
+{capture}
+{profiling}
+
+{check_imports}
+
+/// Reports an error found when "check-errors" is enabled, called with the name of the command, its arguments and the error code.
+#[cfg(feature = "check-errors")]
+pub type ErrorHook = fn(command: &'static str, arguments: fmt::Arguments<'_>, error: GLenum);"#,
+                        Self::generate_extensions(registry, &features)
+                    ),
+                    format!(
+                        r#",
+                        extensions: Extensions,
+                        #[cfg(feature = "check-errors")]
+                        error_hook: Option<ErrorHook>,{check_field}
+                        #[cfg(feature = "capture")]
+                        capture: Option<capture::Capture>,
+                        #[cfg(feature = "profiling")]
+                        counters: profiling::Counters"#
+                    ),
+                    format!(
+                        r#"/// Returns the extensions supported by the context the functions were loaded from.
+                        pub fn extensions(&self) -> &Extensions {{
+                            &self.extensions
+                        }}
+
+                        {context_methods}
+
+                        {versions_methods}
+
+                        {debug_callback_method}
+
+                        /// Replaces the default reporting of the errors found when "check-errors" is enabled.
+                        ///
+                        /// By default the errors are logged with tracing when "trace-errors" is enabled, otherwise they panic.
+                        #[cfg(feature = "check-errors")]
+                        pub fn set_error_hook(&mut self, hook: ErrorHook) {{
+                            self.error_hook = Some(hook);
+                        }}
+
+                        {check_error}
+
+                        /// Captures the commands called from now on, replacing the current capture.
+                        #[cfg(feature = "capture")]
+                        pub fn start_capture(&mut self, capture: capture::Capture) -> Option<capture::Capture> {{
+                            self.capture.replace(capture)
+                        }}
+
+                        /// Returns the number of calls and the time spent in each command called since loading or the last reset.
+                        #[cfg(feature = "profiling")]
+                        pub fn stats(&self) -> profiling::Stats {{
+                            self.counters.stats()
+                        }}
+
+                        /// Resets the number of calls and the time spent in each command.
+                        #[cfg(feature = "profiling")]
+                        pub fn reset_stats(&self) {{
+                            self.counters.reset()
+                        }}
+
+                        #[cfg(feature = "profiling")]
+                        #[inline(always)]
+                        fn timed<T>(&self, index: usize, call: impl FnOnce() -> T) -> T {{
+                            let start = std::time::Instant::now();
+                            let result = call();
+                            self.counters.record(index, start.elapsed());
+                            result
+                        }}
+
+                        #[cfg(not(feature = "profiling"))]
+                        #[inline(always)]
+                        fn timed<T>(&self, _index: usize, call: impl FnOnce() -> T) -> T {{
+                            call()
+                        }}
+
+                        /// Stops capturing the commands called, returning the current capture so it can be finished.
+                        #[cfg(feature = "capture")]
+                        pub fn stop_capture(&mut self) -> Option<capture::Capture> {{
+                            self.capture.take()
+                        }}"#
+                    ),
+                    format!(
+                        r#"let mut gl = Self {{
+                            {formated_constructor},
+                            max_version,
+                            extensions: Extensions::default(),
+                            #[cfg(feature = "check-errors")]
+                            error_hook: None,{check_init}
+                            #[cfg(feature = "capture")]
+                            capture: None,
+                            #[cfg(feature = "profiling")]
+                            counters: profiling::Counters::default()
+                        }};
+
+                        gl.extensions = Extensions::load(&gl);
+
+                        Ok(gl)"#
+                    ),
+                )
+            }
+            _ => (
+                String::new(),
+                String::new(),
+                String::new(),
+                format!(
+                    r#"Ok(Self {{
+                        {formated_constructor},
+                        max_version
+                    }})"#
+                ),
+            ),
+        };
+
+        // A standalone module isn't part of a crate declaring the optional features, e.g. "std", they are only enabled if the including crate has them.
+        let attributes = match output {
+            Output::Crate => format!(
+                "\n{}\n",
+                LINTS
+                    .iter()
+                    .format_with("\n", |lint, f| f(&format_args!("#![allow({lint})]")))
+            ),
+            Output::Module => format!(
+                r#"
+// Declare the module with the lints it needs, e.g.:
+// #[allow({}, unexpected_cfgs)]
+// mod {1} {{ include!(concat!(env!("OUT_DIR"), "/{1}.rs")); }}
+"#,
+                LINTS.join(", "),
+                api.registry()
+            ),
+        };
+
+        // The bindings are derived from the registry, so its notice is carried over.
+        let notice = match &registry.notice {
+            Some(notice) => {
+                let license = match &notice.license {
+                    Some(license) => format!("\n    SPDX-License-Identifier: {license}"),
+                    None => String::new(),
+                };
+
+                format!(
+                    "\n\n    Derived from the Khronos {}.xml registry:\n    {}{license}",
+                    api.registry(),
+                    notice.copyright
+                )
+            }
+            None => String::new(),
+        };
+
+        let header = format!(
+            r#"
+/*
+    DO NOT MANUALLY EDIT THIS FILE.
+    EDITING THIS FILES CAN LEAD TO SAFETY BUGS AND MEMORY CORRUPTION.
+
+    Generated for {api:?} {version:.1} ({profile:?} profile).{notice}
+*/
+{attributes}"#
+        );
+
+        let prelude = r#"
+#[cfg(not(feature = "std"))]
+use core::{
+    ffi::{c_void, CStr},
+    fmt,
+    mem::transmute,
+};
+#[cfg(feature = "std")]
+use std::{ffi::CStr, fmt, mem::transmute, os::raw::c_void};
+
+// Commands can use plain c types instead of the ones defined by the registry.
+#[allow(unused_imports)]
+#[cfg(not(feature = "std"))]
+use core::ffi::{c_char, c_double, c_float, c_int, c_long, c_short, c_uchar, c_uint, c_ulong, c_ushort};
+#[allow(unused_imports)]
+#[cfg(feature = "std")]
+use std::os::raw::{c_char, c_double, c_float, c_int, c_long, c_short, c_uchar, c_uint, c_ulong, c_ushort};
+
+
+#[cfg(all(debug_assertions, feature = "trace-calls"))]
+use tracing::trace;
+
+pub type Result<T, E = LoadError> = core::result::Result<T, E>;
+
+#[derive(Debug)]
+pub struct LoadError {
+    pub name: &'static str,
+    pub pointer: usize,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LoadError {}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to load function \"{}\", expected a valid pointer instead got {}",
+            self.name, self.pointer
+        )
+    }
+}"#
+        .to_string();
+
+        let loader_items = format!(
+            r#"pub struct {loader} {{
+{formated_fields},
+max_version: Option<(u32, u32)>{extensions_field}
+}}
+{extensions}
+
+/// Used in place of the commands that were not loaded, see `{loader}::is_loaded`.
+///
+/// The panic can't unwind out of an `extern "system"` function, so calling a command that was not loaded aborts the process.
+extern "system" fn not_loaded() -> ! {{
+    panic!("Called a command that was not loaded, the context doesn't support the version introducing it")
+}}
+
+unsafe fn load_pointer<F>(loader_function: &mut F, name: &'static [u8]) -> Result<*const c_void>
+where
+    F: FnMut(&CStr) -> *const c_void,
+{{
+    let pointer = loader_function(CStr::from_bytes_with_nul_unchecked(name));
+    let pointer_usize = pointer as usize;
+
+    if pointer_usize == core::usize::MAX || pointer_usize < 8 {{
+        Err(LoadError {{
+            name: core::str::from_utf8_unchecked(&name[..name.len() - 1]),
+            pointer: pointer_usize,
+        }})
+    }} else {{
+        Ok(pointer)
+    }}
+}}"#
+        );
+
+        let loaded_arms = registry
+            .gl_commands
+            .iter()
+            .format_with("\n", |gl_command, f| {
+                let command_name = &gl_command.name;
+                let command_cfg = cfg(command_name);
+
+                f(&format_args!(
+                    r#"{command_cfg}"{command_name}" => self.{command_name} as usize,"#
+                ))
+            });
+
+        let loader_methods = format!(
+            r#"pub unsafe fn load<F>(loader_function: F) -> Result<Self>
+    where
+        F: FnMut(&CStr) -> *const c_void,
+    {{
+        Self::load_inner(loader_function, None)
+    }}
+
+    /// Loads only the commands introduced up to the given version, the ones introduced after it abort the process when called.
+    pub unsafe fn load_up_to<F>(loader_function: F, max_version: (u32, u32)) -> Result<Self>
+    where
+        F: FnMut(&CStr) -> *const c_void,
+    {{
+        Self::load_inner(loader_function, Some(max_version))
+    }}
+
+    unsafe fn load_inner<F>(mut loader_function: F, max_version: Option<(u32, u32)>) -> Result<Self>
+    where
+        F: FnMut(&CStr) -> *const c_void,
+    {{
+        let mut load_pointer = |name: &'static [u8], version: Option<(u32, u32)>, optional: bool| -> Result<*const c_void> {{
+            match (version, max_version) {{
+                (Some(version), Some(max_version)) if version > max_version => Ok(not_loaded as *const c_void),
+                // The commands of the extensions are only usable once the extension is detected, the context may lack them.
+                _ if optional => Ok(load_pointer(&mut loader_function, name).unwrap_or(not_loaded as *const c_void)),
+                _ => load_pointer(&mut loader_function, name),
+            }}
+        }};
+
+        {load_body}
+    }}
+
+    /// Returns whether a command was loaded, calling one that wasn't aborts the process.
+    ///
+    /// The name is the one of the registry, e.g. "glDrawArrays", the commands that are not part of the generated code are never loaded.
+    pub fn is_loaded(&self, name: &str) -> bool {{
+        let pointer = match name {{
+            {loaded_arms}
+            _ => return false,
+        }};
+
+        pointer != not_loaded as *const c_void as usize
+    }}
+
+    /// Returns the version the commands were loaded up to, `None` if all of them were loaded.
+    pub fn max_version(&self) -> Option<(u32, u32)> {{
+        self.max_version
+    }}
+
+    {extensions_methods}"#
+        );
+
+        Parts {
+            loader,
+            header,
+            prelude,
+            types,
+            enums: formated_enums.to_string(),
+            loader_items,
+            loader_methods,
+            commands: formated_methods.to_string(),
+        }
     }
 }
@@ -1,2 +1,10 @@
+pub mod config;
+pub mod diff;
+pub mod export;
+pub mod filter;
 pub mod generator;
+pub mod license;
+pub mod manifest;
 pub mod parser;
+pub mod query;
+pub mod scan;
@@ -0,0 +1,93 @@
+use std::{
+    borrow::Cow,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const LICENSE_APACHE: &str = include_str!("template/LICENSE-APACHE");
+const LICENSE_MIT: &str = include_str!("template/LICENSE-MIT");
+
+/// The license of the generated crate, the texts of the standard ones are embedded in the generator.
+#[derive(Clone, Debug)]
+pub enum License {
+    None,
+    Mit,
+    Apache,
+    /// MIT or Apache-2.0, at the user's option.
+    Dual,
+    /// A license file written by the user.
+    Custom(PathBuf),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown license {0}, expected none, mit, apache, dual or the path to a license file")]
+pub struct LicenseFromStrError(String);
+
+/// Parses "none", "mit", "apache" or "dual", or the path to a custom license file.
+///
+/// Only values containing a path separator or naming an existing file are paths, so a misspelled name is an error.
+impl FromStr for License {
+    type Err = LicenseFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "mit" => Ok(Self::Mit),
+            "apache" | "apache-2.0" => Ok(Self::Apache),
+            "dual" => Ok(Self::Dual),
+            _ if s.contains(['/', std::path::MAIN_SEPARATOR]) || Path::new(s).exists() => {
+                Ok(Self::Custom(s.into()))
+            }
+            _ => Err(LicenseFromStrError(s.to_string())),
+        }
+    }
+}
+
+impl License {
+    /// The spdx expression to put in the manifest, custom licenses use `license-file` instead.
+    pub fn spdx(&self) -> Option<&'static str> {
+        match self {
+            Self::Mit => Some("MIT"),
+            Self::Apache => Some("Apache-2.0"),
+            Self::Dual => Some("MIT OR Apache-2.0"),
+            Self::None | Self::Custom(_) => None,
+        }
+    }
+
+    /// The name of the custom license file once copied to the generated crate.
+    pub fn file_name(&self) -> Option<String> {
+        match self {
+            Self::Custom(path) => Some(
+                path.file_name()
+                    .map_or("LICENSE".into(), |name| name.to_string_lossy().into_owned()),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the license files to write to the generated crate along with their content.
+    pub fn files(&self) -> io::Result<Vec<(String, Cow<'static, str>)>> {
+        let apache = || ("LICENSE-APACHE".to_string(), Cow::Borrowed(LICENSE_APACHE));
+        let mit = || ("LICENSE-MIT".to_string(), Cow::Borrowed(LICENSE_MIT));
+
+        Ok(match self {
+            Self::None => Vec::new(),
+            Self::Mit => vec![mit()],
+            Self::Apache => vec![apache()],
+            Self::Dual => vec![apache(), mit()],
+            Self::Custom(path) => vec![(
+                self.file_name().unwrap_or_default(),
+                Cow::Owned(fs::read_to_string(path)?),
+            )],
+        })
+    }
+
+    /// Resolves the path of a custom license file from a directory.
+    pub fn relative_to(self, directory: &Path) -> Self {
+        match self {
+            Self::Custom(path) => Self::Custom(directory.join(path)),
+            license => license,
+        }
+    }
+}
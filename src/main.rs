@@ -1,72 +1,693 @@
 use angel_generator::{
-    generator::Generator,
+    config::Config,
+    export::{Export, Selection},
+    filter::{Filter, Reduction},
+    generator::{Generator, Naming, Output},
+    license::License,
+    manifest::Manifest,
     parser::{Api, GlProfile, GlRegistry},
+    scan::{Usage, UsageReport},
 };
-use clap::Parser;
-use color_eyre::Result;
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use itertools::Itertools;
 use std::{
+    convert::Infallible,
     fs,
+    io::Write,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, Stdio},
+    str::FromStr,
+    thread,
 };
+use toml_edit::Document;
 
 const GL_XML: &str = include_str!("gl.xml");
+const EGL_XML: &str = include_str!("egl.xml");
+const GLX_XML: &str = include_str!("glx.xml");
+const WGL_XML: &str = include_str!("wgl.xml");
 
 /// An overly complicated opengl generator.
 #[derive(Parser)]
 struct Args {
-    // The folder where to put the generate files.
-    #[arg(short, long)]
-    out: PathBuf,
+    #[command(subcommand)]
+    command: Subcommand,
+}
+
+#[derive(clap::Subcommand)]
+enum Subcommand {
+    /// Generate the bindings crate.
+    Generate(GenerateArgs),
+    /// Show which versions and extensions introduce or remove an enum or command, along with its definition.
+    Query(QueryArgs),
+    /// Export the registry, reduced to a version of the api by default, see docs/export.md for the schema.
+    Export(ExportArgs),
+    /// Compare the enums, commands and extensions of two registries, or two versions and profiles of an api.
+    Diff(DiffArgs),
+}
+
+/// The format of the reports printed by the subcommands.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// A registry xml file: the bundled one, the latest one or a local file.
+#[derive(Clone)]
+enum Source {
+    Bundled,
+    Fetch,
+    Path(PathBuf),
+}
+
+impl FromStr for Source {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bundled" => Ok(Self::Bundled),
+            "fetch" => Ok(Self::Fetch),
+            path => Ok(Self::Path(path.into())),
+        }
+    }
+}
+
+impl Source {
+    /// Reads and parses the registry of the api, the gl family apis share the same registry.
+    fn load(&self, api: Api) -> Result<GlRegistry> {
+        let (registry_url, bundled_xml) = match api {
+            Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => (
+                "https://github.com/KhronosGroup/OpenGL-Registry/raw/main/xml/gl.xml",
+                GL_XML,
+            ),
+            Api::Egl => (
+                "https://github.com/KhronosGroup/EGL-Registry/raw/main/api/egl.xml",
+                EGL_XML,
+            ),
+            Api::Glx => (
+                "https://github.com/KhronosGroup/OpenGL-Registry/raw/main/xml/glx.xml",
+                GLX_XML,
+            ),
+            Api::Wgl => (
+                "https://github.com/KhronosGroup/OpenGL-Registry/raw/main/xml/wgl.xml",
+                WGL_XML,
+            ),
+        };
+
+        // TODO: Proper error handling instead of propagating errors.
+        let registry_xml: String = match self {
+            Source::Bundled => bundled_xml.to_string(),
+            Source::Fetch => reqwest::blocking::get(registry_url)?.text()?,
+            Source::Path(path) => fs::read_to_string(path.canonicalize()?)?,
+        };
+
+        Ok(GlRegistry::parse(&registry_xml)?)
+    }
+}
+
+/// Where to read the registry from.
+#[derive(clap::Args)]
+struct RegistryArgs {
     /// Path to the registry xml file, ignored if used in conjunction with --fetch.
     #[arg(short, long)]
     path: Option<PathBuf>,
     /// Fetch the latest registry xml instead of using the bundled version.
     #[arg(short, long)]
     fetch: bool,
+    /// The api to use: gl, gles1, gles2, glsc2, egl, glx or wgl.
+    #[arg(short, long, default_value = "gl")]
+    api: Api,
+}
+
+impl RegistryArgs {
+    fn load(&self) -> Result<GlRegistry> {
+        let source = if self.fetch {
+            Source::Fetch
+        } else if let Some(path) = &self.path {
+            Source::Path(path.clone())
+        } else {
+            Source::Bundled
+        };
+
+        source.load(self.api)
+    }
+}
+
+#[derive(clap::Args)]
+struct GenerateArgs {
+    // The folder where to put the generate files, required unless the target sets it.
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// Only write the bindings module to this file, "-" writes it to stdout.
+    ///
+    /// No Cargo.toml, lib.rs or license is written and every selected version and extension is enabled without
+    /// cargo features, so the module can be dropped into another crate or included with include!.
+    #[arg(long, conflicts_with = "out")]
+    module: Option<PathBuf>,
+    /// Split the bindings into types.rs, enums.rs, loader.rs and commands.rs, declared by a mod.rs.
+    #[arg(long, conflicts_with = "module")]
+    split: bool,
+    /// Generate a target of the config file, the options given on the command line override its settings.
+    #[arg(short, long)]
+    target: Option<String>,
+    /// The config file defining the targets.
+    #[arg(long, default_value = "angel.toml")]
+    config: PathBuf,
+    #[command(flatten)]
+    registry: RegistryArgs,
+    #[command(flatten)]
+    selection: SelectionArgs,
+    /// Only generate the enums and commands used by the rust sources in this file or directory.
+    #[arg(long)]
+    scan: Option<PathBuf>,
+    /// How the methods calling the commands are named: stripped of the api prefix, e.g. GetString, or original, e.g. glGetString.
+    #[arg(long, default_value = "stripped")]
+    naming: Naming,
+    #[command(flatten)]
+    manifest: ManifestArgs,
     #[arg(short, long)]
     verbose: bool,
 }
 
+/// The package settings of the generated crate, the ones not given keep the value of an existing Cargo.toml.
+#[derive(clap::Args)]
+struct ManifestArgs {
+    /// The name of the generated crate, defaults to angel.
+    #[arg(long)]
+    crate_name: Option<String>,
+    /// The version of the generated crate, defaults to 0.1.0-alpha.2.
+    #[arg(long)]
+    crate_version: Option<String>,
+    /// The rust edition of the generated crate, defaults to 2021.
+    #[arg(long)]
+    edition: Option<String>,
+    /// The license of the generated crate: none, mit, apache, dual or the path to a custom license file.
+    #[arg(long, default_value = "apache")]
+    license: License,
+    /// Don't add the optional tracing dependency, the trace-* features are removed.
+    #[arg(long)]
+    no_tracing: bool,
+}
+
+/// Which version, profile and extensions of the api to keep.
+#[derive(clap::Args)]
+struct SelectionArgs {
+    /// The version of the api, defaults to the latest one found in the registry.
+    #[arg(long)]
+    version: Option<f32>,
+    /// The profile of the api: core, compatibility or common.
+    #[arg(long, default_value = "core")]
+    profile: GlProfile,
+    /// A comma separated list of extensions to include, each one gets its own cargo feature.
+    #[arg(short, long, value_delimiter = ',')]
+    extensions: Vec<String>,
+    /// Include all the extensions supported by the api and profile.
+    #[arg(long)]
+    all_extensions: bool,
+    /// A comma separated list of enums and commands to add to the selection, exact names or glob patterns.
+    #[arg(long, value_delimiter = ',')]
+    include: Vec<String>,
+    /// A comma separated list of enums and commands to remove from the selection, exact names or glob patterns.
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+}
+
+impl SelectionArgs {
+    /// Reduces the registry to the selection, returning the selected version and extensions.
+    fn reduce(&self, gl_registry: &mut GlRegistry, api: Api) -> Result<(f32, Vec<String>)> {
+        let profile = self.profile;
+
+        let extensions = if self.all_extensions {
+            gl_registry
+                .gl_extensions
+                .iter()
+                .filter(|gl_extension| gl_extension.supports(api, profile))
+                .map(|gl_extension| gl_extension.name.clone())
+                .collect()
+        } else {
+            self.extensions.clone()
+        };
+
+        // The extensions the api and profile can't use are dropped by the reduction, unknown ones are likely typos.
+        for name in &extensions {
+            match gl_registry
+                .gl_extensions
+                .iter()
+                .find(|gl_extension| &gl_extension.name == name)
+            {
+                Some(gl_extension) if !gl_extension.supports(api, profile) => eprintln!(
+                    "warning: {name} is not supported by {} with the {profile:?} profile, it is ignored",
+                    api.api()
+                ),
+                Some(_) => {}
+                None => {
+                    return Err(match gl_registry.suggest_extension(name, 3).as_slice() {
+                        [] => eyre!("Unknown extension {name}"),
+                        suggestions => eyre!(
+                            "Unknown extension {name}, did you mean: {}?",
+                            suggestions.join(", ")
+                        ),
+                    })
+                }
+            }
+        }
+
+        let mut filter = Filter::new();
+
+        for pattern in &self.include {
+            filter = filter.include(pattern)?;
+        }
+
+        for pattern in &self.exclude {
+            filter = filter.exclude(pattern)?;
+        }
+
+        for pattern in filter.unknown_patterns(gl_registry) {
+            match gl_registry.suggest(pattern, 3).as_slice() {
+                [] => eprintln!("warning: {pattern} doesn't match any enum or command"),
+                suggestions => eprintln!(
+                    "warning: {pattern} doesn't match any enum or command, did you mean: {}?",
+                    suggestions.join(", ")
+                ),
+            }
+        }
+
+        let mut reduction = Reduction::new(api)
+            .profile(profile)
+            .extensions(extensions.iter().cloned())
+            .filter(filter);
+
+        if let Some(version) = self.version {
+            reduction = reduction.version(version);
+        }
+
+        let version = reduction
+            .reduce(gl_registry)
+            .ok_or_else(|| eyre!("The registry doesn't define any version of {}", api.api()))?;
+
+        Ok((version, extensions))
+    }
+}
+
+#[derive(clap::Args)]
+struct QueryArgs {
+    /// The name of the enum or command, the case and the api prefix can be omitted.
+    name: String,
+    #[command(flatten)]
+    registry: RegistryArgs,
+}
+
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// The registry to compare from: "bundled", "fetch" or the path to a registry xml file.
+    #[arg(default_value = "bundled")]
+    old: Source,
+    /// The registry to compare to: "bundled", "fetch" or the path to a registry xml file.
+    #[arg(default_value = "bundled")]
+    new: Source,
+    /// The api to use: gl, gles1, gles2, glsc2, egl, glx or wgl.
+    #[arg(short, long, default_value = "gl")]
+    api: Api,
+    /// Only compare the enums and commands required by this version of the api in the old registry.
+    #[arg(long)]
+    old_version: Option<f32>,
+    /// Only compare the enums and commands required by this version of the api in the new registry.
+    #[arg(long)]
+    new_version: Option<f32>,
+    /// The profile of the old version, defaults to core.
+    #[arg(long)]
+    old_profile: Option<GlProfile>,
+    /// The profile of the new version, defaults to core.
+    #[arg(long)]
+    new_profile: Option<GlProfile>,
+    #[arg(long, value_enum, default_value = "text")]
+    format: Format,
+}
+
+/// The formats the registry can be exported to.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ExportFormat {
+    Json,
+}
+
+#[derive(clap::Args)]
+struct ExportArgs {
+    /// The file to write to, defaults to the standard output.
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    #[command(flatten)]
+    registry: RegistryArgs,
+    #[command(flatten)]
+    selection: SelectionArgs,
+    /// Export the whole registry instead of reducing it to a version, profile and extensions.
+    #[arg(long)]
+    whole_registry: bool,
+    #[arg(long, value_enum, default_value = "json")]
+    format: ExportFormat,
+}
+
+impl GenerateArgs {
+    /// Fills the options not given on the command line with the settings of the target.
+    fn apply_target(&mut self, name: &str, matches: &ArgMatches) -> Result<()> {
+        let toml = fs::read_to_string(&self.config)
+            .wrap_err_with(|| format!("Failed to read {}", self.config.display()))?;
+        let directory = self.config.parent().unwrap_or_else(|| Path::new(""));
+        let config = Config::parse(&toml, directory)?;
+
+        let target = config.target(name).ok_or_else(|| {
+            eyre!(
+                "No target named {name} in {}, available targets: {}",
+                self.config.display(),
+                config.targets.iter().map(|target| &target.name).join(", ")
+            )
+        })?;
+
+        // Only the options coming from the command line take precedence, not their default values.
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if !given("out") && target.out.is_some() {
+            self.out = target.out.clone();
+        }
+        if !given("module") && !given("out") && target.module.is_some() {
+            self.module = target.module.clone();
+        }
+        if !given("split") {
+            self.split = target.split.unwrap_or(self.split);
+        }
+        if !given("scan") && target.scan.is_some() {
+            self.scan = target.scan.clone();
+        }
+        if !given("naming") {
+            self.naming = target.naming.unwrap_or(self.naming);
+        }
+        if !given("api") {
+            self.registry.api = target.api.unwrap_or(self.registry.api);
+        }
+        if !given("path") && !given("fetch") {
+            match target.registry.as_deref() {
+                Some("bundled") | None => {}
+                Some("fetch") => self.registry.fetch = true,
+                Some(path) => self.registry.path = Some(path.into()),
+            }
+        }
+        if !given("version") && target.version.is_some() {
+            self.selection.version = target.version;
+        }
+        if !given("profile") {
+            self.selection.profile = target.profile.unwrap_or(self.selection.profile);
+        }
+        if !given("all_extensions") {
+            self.selection.all_extensions = target
+                .all_extensions
+                .unwrap_or(self.selection.all_extensions);
+        }
+
+        if let (false, Some(license)) = (given("license"), &target.license) {
+            self.manifest.license = license.clone();
+        }
+        if !given("no_tracing") {
+            self.manifest.no_tracing = target
+                .tracing
+                .map_or(self.manifest.no_tracing, |tracing| !tracing);
+        }
+
+        for (id, option, setting) in [
+            (
+                "crate_name",
+                &mut self.manifest.crate_name,
+                &target.crate_name,
+            ),
+            (
+                "crate_version",
+                &mut self.manifest.crate_version,
+                &target.crate_version,
+            ),
+            ("edition", &mut self.manifest.edition, &target.edition),
+        ] {
+            if let (false, Some(setting)) = (given(id), setting) {
+                *option = Some(setting.clone());
+            }
+        }
+
+        for (id, option, setting) in [
+            (
+                "extensions",
+                &mut self.selection.extensions,
+                &target.extensions,
+            ),
+            ("include", &mut self.selection.include, &target.include),
+            ("exclude", &mut self.selection.exclude, &target.exclude),
+        ] {
+            if let (false, Some(setting)) = (given(id), setting) {
+                *option = setting.clone();
+            }
+        }
+
+        Ok(())
+    }
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
 
-    // TODO: Proper error handling instead of propagating errors.
-    let registry_xml: String = {
-        if args.fetch {
-            reqwest::blocking::get(
-                "https://github.com/KhronosGroup/OpenGL-Registry/raw/main/xml/gl.xml",
-            )?
-            .text()?
-        } else if let Some(path) = args.path {
-            fs::read_to_string(path.canonicalize()?)?
-        } else {
-            GL_XML.to_string()
+    match Args::from_arg_matches(&matches)?.command {
+        Subcommand::Generate(mut args) => {
+            if let (Some(target), Some((_, matches))) = (args.target.clone(), matches.subcommand())
+            {
+                args.apply_target(&target, matches)?;
+            }
+
+            generate(args)
+        }
+        Subcommand::Query(args) => query(args),
+        Subcommand::Diff(args) => diff(args),
+        Subcommand::Export(args) => export(args),
+    }
+}
+
+fn export(args: ExportArgs) -> Result<()> {
+    let api = args.registry.api;
+
+    let mut gl_registry = args.registry.load()?;
+
+    let selected = if args.whole_registry {
+        None
+    } else {
+        Some(args.selection.reduce(&mut gl_registry, api)?)
+    };
+
+    let selection = selected.as_ref().map(|(version, extensions)| Selection {
+        api,
+        version: *version,
+        profile: args.selection.profile,
+        extensions,
+        include: &args.selection.include,
+        exclude: &args.selection.exclude,
+    });
+
+    let exported = match args.format {
+        ExportFormat::Json => Export::new(&gl_registry, selection).to_json()?,
+    };
+
+    match &args.out {
+        Some(out) => fs::write(out, exported)?,
+        None => println!("{exported}"),
+    }
+
+    Ok(())
+}
+
+fn diff(args: DiffArgs) -> Result<()> {
+    let api = args.api;
+
+    let mut old = args.old.load(api)?;
+    let mut new = args.new.load(api)?;
+
+    // Without a version or profile the registries are compared as a whole, extensions included.
+    if args.old_version.is_some()
+        || args.new_version.is_some()
+        || args.old_profile.is_some()
+        || args.new_profile.is_some()
+    {
+        for (gl_registry, version, profile) in [
+            (&mut old, args.old_version, args.old_profile),
+            (&mut new, args.new_version, args.new_profile),
+        ] {
+            let version = match version {
+                Some(version) => version,
+                None => gl_registry.latest_version(api).ok_or_else(|| {
+                    eyre!("The registry doesn't define any version of {}", api.api())
+                })?,
+            };
+
+            gl_registry.reduce(api, version, profile.unwrap_or(GlProfile::Core), &[]);
         }
+    }
+
+    let diff = old.diff(&new);
+
+    match args.format {
+        Format::Text => println!("{diff}"),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+    }
+
+    Ok(())
+}
+
+fn query(args: QueryArgs) -> Result<()> {
+    let gl_registry = args.registry.load()?;
+
+    match gl_registry.query(&args.name) {
+        Some(result) => println!("{result}"),
+        None => {
+            let suggestions = gl_registry.suggest(&args.name, 5);
+
+            if suggestions.is_empty() {
+                return Err(eyre!("No enum or command named {}", args.name));
+            }
+
+            return Err(eyre!(
+                "No enum or command named {}, did you mean: {}?",
+                args.name,
+                suggestions.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn generate(args: GenerateArgs) -> Result<()> {
+    let api = args.registry.api;
+    let profile = args.selection.profile;
+
+    let mut gl_registry = args.registry.load()?;
+
+    // The sources are resolved against the whole registry, so the enums and commands the selection lacks can be reported.
+    let referenced = match &args.scan {
+        Some(path) => Some(Usage::scan_path(path)?.resolve(&gl_registry, api)),
+        None => None,
     };
 
-    let mut gl_registry = GlRegistry::parse(&registry_xml)?;
-    gl_registry.reduce(Api::Gl, 4.6, GlProfile::Core);
+    let (version, _) = args.selection.reduce(&mut gl_registry, api)?;
+
+    if let Some(referenced) = referenced {
+        let report = UsageReport::new(&referenced, &gl_registry);
+
+        eprintln!(
+            "Found {} commands and {} enums used",
+            report.commands.len(),
+            report.enums.len()
+        );
 
-    let generated = Generator::generate(&gl_registry, Api::Gl, 4.6, GlProfile::Core);
-    let cargo_toml = include_str!("template/Cargo.toml");
-    let lib_rs = include_str!("template/lib.rs");
+        for name in &report.missing {
+            eprintln!(
+                "warning: {name} is used but not provided by {} {version:.1} and the selected extensions",
+                api.api()
+            );
+        }
 
-    let output_folder = &args.out;
+        gl_registry.retain(|name| referenced.contains(name));
+    }
 
-    if output_folder.exists() {
-        fs::remove_dir_all(output_folder)?;
+    if let Some(module) = &args.module {
+        let generated = Generator::generate(
+            &gl_registry,
+            api,
+            version,
+            profile,
+            Output::Module,
+            args.naming,
+        );
+        return write_module(module, generated);
     }
 
+    // The requested version is enabled by default.
+    let manifest = Manifest {
+        name: args.manifest.crate_name,
+        version: args.manifest.crate_version,
+        edition: args.manifest.edition,
+        license: args.manifest.license,
+        tracing: !args.manifest.no_tracing,
+        ..Manifest::new(&gl_registry, api, version)
+    };
+
+    // Apis exclusive to a single platform, like wgl, still generate everywhere but only compile on their platform.
+    let cfg = match api.target_os() {
+        Some(target_os) => format!("#[cfg(target_os = \"{target_os}\")]\n"),
+        None => String::new(),
+    };
+    let lib_rs = include_str!("template/lib.rs")
+        .replace("{module}", api.registry())
+        .replace("{cfg}", &cfg);
+
+    let output_folder = args.out.as_ref().ok_or_else(|| {
+        eyre!("No output folder, use --out or --module or set them in the target")
+    })?;
+
+    let licenses = manifest
+        .license
+        .files()
+        .wrap_err("Failed to read the license file")?;
+
+    // An existing manifest is updated rather than replaced, so the user edits are kept.
+    let cargo_toml_path = output_folder.join("Cargo.toml");
+    let cargo_toml = if cargo_toml_path.exists() {
+        let mut cargo_toml: Document = fs::read_to_string(&cargo_toml_path)?
+            .parse()
+            .wrap_err_with(|| format!("Failed to parse {}", cargo_toml_path.display()))?;
+        manifest.update(&mut cargo_toml);
+        cargo_toml
+    } else {
+        manifest.to_document()
+    };
+
     fs::create_dir_all(output_folder)?;
     fs::create_dir_all(output_folder.join("src"))?;
-    fs::write(output_folder.join("src/gl.rs"), generated)?;
+
+    // Switching layouts leaves the files of the previous one behind, they would conflict with the new ones.
+    let single_file = output_folder.join(format!("src/{}.rs", api.registry()));
+    let split_folder = output_folder.join("src").join(api.registry());
+
+    if args.split {
+        if single_file.exists() {
+            fs::remove_file(&single_file)?;
+        }
+
+        fs::create_dir_all(&split_folder)?;
+
+        for (file_name, generated) in
+            Generator::generate_split(&gl_registry, api, version, profile, args.naming)
+        {
+            fs::write(split_folder.join(file_name), generated)?;
+        }
+    } else {
+        if split_folder.exists() {
+            fs::remove_dir_all(&split_folder)?;
+        }
+
+        let generated = Generator::generate(
+            &gl_registry,
+            api,
+            version,
+            profile,
+            Output::Crate,
+            args.naming,
+        );
+        fs::write(single_file, generated)?;
+    }
     fs::write(output_folder.join("src/lib.rs"), lib_rs)?;
-    fs::write(output_folder.join("Cargo.toml"), cargo_toml)?;
-    fs::copy("LICENSE-APACHE", output_folder.join("LICENSE-APACHE"))?;
-    fs::copy("LICENSE-MIT", output_folder.join("LICENSE-MIT"))?;
+    fs::write(cargo_toml_path, cargo_toml.to_string())?;
+
+    for (file_name, text) in licenses {
+        fs::write(output_folder.join(file_name), text.as_ref())?;
+    }
 
     // let mut src_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     // src_dir.pop();
@@ -94,3 +715,49 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Writes a standalone bindings module to a file or to stdout, formatted with rustfmt when available.
+fn write_module(path: &Path, generated: String) -> Result<()> {
+    let formatted = rustfmt(&generated).unwrap_or_else(|| {
+        eprintln!("Failed to format code");
+        generated
+    });
+
+    if path == Path::new("-") {
+        print!("{formatted}");
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, formatted)
+            .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Formats the code by piping it through rustfmt, returns `None` if rustfmt is missing or fails.
+fn rustfmt(code: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // The code is written from another thread so rustfmt can't block on a full stdout pipe.
+    let mut stdin = child.stdin.take()?;
+    let code = code.to_string();
+    let writer = thread::spawn(move || stdin.write_all(code.as_bytes()));
+
+    let output = child.wait_with_output().ok()?;
+    writer.join().ok()?.ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,427 @@
+use toml_edit::{value, Array, Document, InlineTable, Item, Table};
+
+use crate::{
+    generator::Generator,
+    license::License,
+    parser::{Api, GlRegistry},
+};
+
+/// The features every generated crate has, along with the features they enable.
+const FEATURES: [(&str, &[&str]); 5] = [
+    ("capture", &["std"]),
+    ("check-errors", &[]),
+    ("mock", &["std"]),
+    ("profiling", &["std"]),
+    ("std", &[]),
+];
+
+// The package keys of the angel crate, they are only added when missing so they can be edited, and only to the crate named "angel".
+const DESCRIPTION: &str = "Another OpenGL extension loader";
+const AUTHORS: [&str; 1] = ["Matilde Morrone <contact@morrone.dev>"];
+const LINKS: [(&str, &str); 3] = [
+    ("repository", "https://github.com/morr0ne/angel"),
+    ("readme", "../README.md"),
+    ("documentation", "https://docs.rs/angel"),
+];
+
+/// The features using the optional tracing dependency.
+const TRACING_FEATURES: [(&str, &[&str]); 3] = [
+    ("trace-errors", &["check-errors", "dep:tracing"]),
+    ("trace-calls", &["dep:tracing"]),
+    ("trace-messages", &["dep:tracing"]),
+];
+
+/// The manifest of the generated crate.
+///
+/// The package settings left to `None` keep the value of the existing manifest, if any, or use a default one.
+pub struct Manifest {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub edition: Option<String>,
+    /// The license of the crate, the license keys are left as is with [`License::None`].
+    pub license: License,
+    /// Whether to add the optional tracing dependency and the features using it.
+    pub tracing: bool,
+    /// The features enabling each version of the api, the previous version included, and each extension.
+    pub features: Vec<(String, Vec<String>)>,
+    /// The feature of the version enabled by default.
+    pub default_version: String,
+}
+
+impl Manifest {
+    /// Creates the manifest of a crate generated from the reduced registry.
+    pub fn new(registry: &GlRegistry, api: Api, version: f32) -> Self {
+        Self {
+            name: None,
+            version: None,
+            edition: None,
+            license: License::Apache,
+            tracing: true,
+            features: Generator::features(registry, api),
+            default_version: api.version_feature(version),
+        }
+    }
+
+    /// Creates a new manifest, see [`Manifest::update`].
+    pub fn to_document(&self) -> Document {
+        let mut document = Document::new();
+        self.update(&mut document);
+        document
+    }
+
+    /// Updates an existing manifest in place, the keys not managed by the generator and the formatting are preserved.
+    ///
+    /// The features generated by a previous run are replaced, they are listed in `package.metadata.angel.generated-features`.
+    pub fn update(&self, document: &mut Document) {
+        let package = table(document.as_item_mut(), "package");
+
+        for (key, setting, default) in [
+            ("name", &self.name, "angel"),
+            ("version", &self.version, "0.1.0-alpha.2"),
+            ("edition", &self.edition, "2021"),
+        ] {
+            match setting {
+                Some(setting) => package[key] = value(setting),
+                None => {
+                    package[key].or_insert(value(default));
+                }
+            }
+        }
+
+        let angel = package.get("name").and_then(|name| name.as_str()) == Some("angel");
+
+        if angel {
+            package["description"].or_insert(value(DESCRIPTION));
+        }
+
+        if let Some((key, license, other)) = match (self.license.spdx(), self.license.file_name()) {
+            (Some(spdx), _) => Some(("license", spdx.to_string(), "license-file")),
+            (_, Some(file_name)) => Some(("license-file", file_name, "license")),
+            _ => None,
+        } {
+            package[key] = value(license);
+
+            if let Some(package) = package.as_table_like_mut() {
+                package.remove(other);
+            }
+        }
+
+        if angel {
+            package["authors"].or_insert(value(Array::from_iter(AUTHORS)));
+
+            for (key, default) in LINKS {
+                package[key].or_insert(value(default));
+            }
+        }
+
+        let previous: Vec<String> = package
+            .get("metadata")
+            .and_then(|metadata| metadata.get("angel"))
+            .and_then(|angel| angel.get("generated-features"))
+            .and_then(|features| features.as_array())
+            .map(|array| {
+                array
+                    .iter()
+                    .filter_map(|feature| feature.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        table(table(package, "metadata"), "angel")["generated-features"] = value(Array::from_iter(
+            self.features.iter().map(|(feature, _)| feature),
+        ));
+
+        let features = table(document.as_item_mut(), "features");
+
+        for feature in &previous {
+            if let Some(features) = features.as_table_like_mut() {
+                features.remove(feature);
+            }
+        }
+
+        // The default version is replaced, the other default features are left as is.
+        let default = features["default"].or_insert(value(Array::from_iter(["std"])));
+
+        if let Some(default) = default.as_array_mut() {
+            remove(default, |feature| {
+                previous.iter().any(|previous| previous == feature)
+            });
+            default.push(&self.default_version);
+        }
+
+        for (feature, implied_features) in FEATURES {
+            features[feature].or_insert(value(Array::from_iter(implied_features.iter().copied())));
+        }
+
+        if self.tracing {
+            features["std"] = value(merge(&features["std"], ["tracing?/std"]));
+
+            for (feature, implied_features) in TRACING_FEATURES {
+                features[feature]
+                    .or_insert(value(Array::from_iter(implied_features.iter().copied())));
+            }
+
+            let mut tracing = InlineTable::new();
+            tracing.insert("version", "0.1.37".into());
+            tracing.insert("optional", true.into());
+            tracing.insert("default-features", false.into());
+
+            table(document.as_item_mut(), "dependencies")["tracing"].or_insert(value(tracing));
+        } else {
+            if let Some(std) = features["std"].as_array_mut() {
+                remove(std, |feature| feature == "tracing?/std");
+            }
+
+            for (feature, _) in TRACING_FEATURES {
+                if let Some(features) = features.as_table_like_mut() {
+                    features.remove(feature);
+                }
+            }
+
+            if let Some(dependencies) = document
+                .get_mut("dependencies")
+                .and_then(Item::as_table_like_mut)
+            {
+                dependencies.remove("tracing");
+            }
+
+            // The generated code still refers to the tracing features, they just can never be enabled.
+            let check_cfg = format!(
+                "cfg(feature, values({}))",
+                TRACING_FEATURES
+                    .iter()
+                    .map(|(feature, _)| format!("{feature:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+
+            let mut unexpected_cfgs = InlineTable::new();
+            unexpected_cfgs.insert("level", "warn".into());
+            unexpected_cfgs.insert("check-cfg", Array::from_iter([check_cfg]).into());
+
+            table(table(document.as_item_mut(), "lints"), "rust")["unexpected_cfgs"]
+                .or_insert(value(unexpected_cfgs));
+        }
+
+        for (feature, implied_features) in &self.features {
+            document["features"][feature] = value(Array::from_iter(implied_features));
+        }
+    }
+}
+
+/// Returns the table under the key, creating it as a standard table rather than an inline one when missing.
+fn table<'a>(item: &'a mut Item, key: &str) -> &'a mut Item {
+    let table = &mut item[key];
+
+    if table.is_none() {
+        let mut new = Table::new();
+        new.set_implicit(true);
+        *table = Item::Table(new);
+    }
+
+    table
+}
+
+/// Returns the array with the values missing from it appended, the values are expected to be strings.
+fn merge<'a>(item: &Item, values: impl IntoIterator<Item = &'a str>) -> Array {
+    let mut array = item.as_array().cloned().unwrap_or_default();
+
+    for new in values {
+        if !array.iter().any(|value| value.as_str() == Some(new)) {
+            array.push(new);
+        }
+    }
+
+    array
+}
+
+/// Removes the string values matching the predicate from the array.
+fn remove(array: &mut Array, mut predicate: impl FnMut(&str) -> bool) {
+    let mut index = 0;
+
+    while index < array.len() {
+        if array
+            .get(index)
+            .and_then(|value| value.as_str())
+            .is_some_and(&mut predicate)
+        {
+            array.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(versions: &[&str]) -> Manifest {
+        Manifest {
+            name: None,
+            version: None,
+            edition: None,
+            license: License::Apache,
+            tracing: true,
+            // Each version feature enables the previous one.
+            features: versions
+                .iter()
+                .enumerate()
+                .map(|(index, version)| {
+                    let previous = versions[..index]
+                        .last()
+                        .map(|previous| previous.to_string());
+                    (version.to_string(), previous.into_iter().collect())
+                })
+                .collect(),
+            default_version: versions.last().unwrap().to_string(),
+        }
+    }
+
+    fn strings(item: &Item) -> Vec<&str> {
+        item.as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn new_manifest() {
+        let document = manifest(&["gl10", "gl11"]).to_document();
+
+        assert_eq!(document["package"]["name"].as_str(), Some("angel"));
+        assert_eq!(
+            document["package"]["version"].as_str(),
+            Some("0.1.0-alpha.2")
+        );
+        assert_eq!(document["package"]["license"].as_str(), Some("Apache-2.0"));
+        assert_eq!(
+            document["package"]["description"].as_str(),
+            Some(DESCRIPTION)
+        );
+        assert_eq!(strings(&document["package"]["authors"]), AUTHORS);
+        assert_eq!(strings(&document["features"]["default"]), ["std", "gl11"]);
+        assert_eq!(strings(&document["features"]["gl11"]), ["gl10"]);
+        assert_eq!(strings(&document["features"]["std"]), ["tracing?/std"]);
+        assert_eq!(
+            strings(&document["package"]["metadata"]["angel"]["generated-features"]),
+            ["gl10", "gl11"]
+        );
+        assert!(document["dependencies"]["tracing"].is_inline_table());
+        assert!(document["package"]["metadata"].is_table());
+    }
+
+    #[test]
+    fn update_keeps_user_edits() {
+        let mut document: Document = r#"# The bindings of our renderer.
+[package]
+name = "renderer-gl"
+version = "1.2.0"
+edition = "2021"
+description = "Our own bindings"
+license-file = "LICENSE"
+
+[package.metadata.angel]
+generated-features = ["gl10", "gl11", "gl12"]
+
+[dependencies]
+bytemuck = "1"
+
+[features]
+default = ["std", "gl12", "fast"]
+std = ["tracing?/std"]
+fast = []
+gl10 = []
+gl11 = ["gl10"]
+gl12 = ["gl11"]
+"#
+        .parse()
+        .unwrap();
+
+        let manifest = Manifest {
+            version: Some("1.3.0".to_string()),
+            license: License::Mit,
+            ..manifest(&["gl10", "gl11"])
+        };
+        manifest.update(&mut document);
+
+        let output = document.to_string();
+        assert!(output.starts_with("# The bindings of our renderer.\n[package]\n"));
+
+        assert_eq!(document["package"]["name"].as_str(), Some("renderer-gl"));
+        assert_eq!(document["package"]["version"].as_str(), Some("1.3.0"));
+        assert_eq!(
+            document["package"]["description"].as_str(),
+            Some("Our own bindings")
+        );
+        assert_eq!(document["package"]["license"].as_str(), Some("MIT"));
+        assert!(document["package"].get("license-file").is_none());
+        // The package keys of angel aren't added to other crates.
+        assert!(document["package"].get("authors").is_none());
+        assert!(document["package"].get("repository").is_none());
+        assert_eq!(document["dependencies"]["bytemuck"].as_str(), Some("1"));
+
+        // The features of the previous run are replaced, the user ones are kept.
+        assert_eq!(
+            strings(&document["features"]["default"]),
+            ["std", "fast", "gl11"]
+        );
+        assert!(document["features"].get("gl12").is_none());
+        assert_eq!(strings(&document["features"]["fast"]), Vec::<&str>::new());
+        assert_eq!(
+            strings(&document["package"]["metadata"]["angel"]["generated-features"]),
+            ["gl10", "gl11"]
+        );
+    }
+
+    #[test]
+    fn new_manifest_with_other_name() {
+        let document = Manifest {
+            name: Some("renderer-gl".to_string()),
+            ..manifest(&["gl10"])
+        }
+        .to_document();
+
+        assert_eq!(document["package"]["name"].as_str(), Some("renderer-gl"));
+        assert!(document["package"].get("description").is_none());
+        assert!(document["package"].get("authors").is_none());
+        assert!(document["package"].get("documentation").is_none());
+    }
+
+    #[test]
+    fn update_without_license() {
+        let mut document: Document = "[package]\nlicense-file = \"LICENSE\"\n".parse().unwrap();
+
+        Manifest {
+            license: License::None,
+            ..manifest(&["gl10"])
+        }
+        .update(&mut document);
+
+        assert_eq!(
+            document["package"]["license-file"].as_str(),
+            Some("LICENSE")
+        );
+        assert!(document["package"].get("license").is_none());
+    }
+
+    #[test]
+    fn update_without_tracing() {
+        let mut document = manifest(&["gl10"]).to_document();
+
+        Manifest {
+            tracing: false,
+            ..manifest(&["gl10"])
+        }
+        .update(&mut document);
+
+        assert!(document
+            .get("dependencies")
+            .and_then(|dependencies| dependencies.get("tracing"))
+            .is_none());
+        assert!(document["features"].get("trace-calls").is_none());
+        assert_eq!(strings(&document["features"]["std"]), Vec::<&str>::new());
+        assert!(document["lints"]["rust"]["unexpected_cfgs"].is_inline_table());
+    }
+}
@@ -1,5 +1,9 @@
-use roxmltree::Document;
-use std::{collections::HashSet, str::FromStr};
+use roxmltree::{Document, Node};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 /// A list of all keywords reserved by the language.
 const KEYWORDS: [&str; 51] = [
@@ -10,15 +14,23 @@ const KEYWORDS: [&str; 51] = [
     "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
 ];
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// A list of names brought into scope by the tracing macros, parameters using them would be shadowed.
+const SHADOWED: [&str; 2] = ["debug", "display"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Api {
     Gl,
     Gles1,
     Gles2,
     Glsc2,
+    Egl,
+    Glx,
+    Wgl,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum GlProfile {
     Core,
     Compatibility,
@@ -55,6 +67,9 @@ impl FromStr for Api {
             "gles1" => Ok(Self::Gles1),
             "gles2" => Ok(Self::Gles2),
             "glsc2" => Ok(Self::Glsc2),
+            "egl" => Ok(Self::Egl),
+            "glx" => Ok(Self::Glx),
+            "wgl" => Ok(Self::Wgl),
             _ => Err(ApiFromStrError),
         }
     }
@@ -67,55 +82,291 @@ impl Api {
             Api::Gles1 => "gles1",
             Api::Gles2 => "gles2",
             Api::Glsc2 => "glsc2",
+            Api::Egl => "egl",
+            Api::Glx => "glx",
+            Api::Wgl => "wgl",
+        }
+    }
+
+    /// The name of the registry file the api is defined in, also used as the name of the generated module.
+    pub const fn registry(&self) -> &str {
+        match self {
+            Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => "gl",
+            Api::Egl => "egl",
+            Api::Glx => "glx",
+            Api::Wgl => "wgl",
+        }
+    }
+
+    /// The prefix shared by all the commands of the api.
+    ///
+    /// Some wgl commands, e.g. "SwapBuffers", are part of the windows api and don't have a prefix.
+    pub const fn prefix(&self) -> &str {
+        match self {
+            Api::Glx => "glX",
+            _ => self.registry(),
+        }
+    }
+
+    /// The name of the cargo feature enabling a version of the api in the generated crate, e.g. "gl46" or "gles32".
+    pub fn version_feature(&self, version: f32) -> String {
+        let name = match self {
+            Api::Gl => "gl",
+            Api::Gles1 | Api::Gles2 => "gles",
+            Api::Glsc2 => "glsc",
+            Api::Egl => "egl",
+            Api::Glx => "glx",
+            Api::Wgl => "wgl",
+        };
+
+        format!("{name}{}", format!("{version:.1}").replace('.', ""))
+    }
+
+    /// The operating system the api is exclusive to, if any.
+    pub const fn target_os(&self) -> Option<&str> {
+        match self {
+            Api::Wgl => Some("windows"),
+            _ => None,
+        }
+    }
+
+    /// The name of the generated function loader.
+    pub const fn loader(&self) -> &'static str {
+        match self {
+            Api::Gl | Api::Gles1 | Api::Gles2 | Api::Glsc2 => "Gl",
+            Api::Egl => "Egl",
+            Api::Glx => "Glx",
+            Api::Wgl => "Wgl",
         }
     }
 }
 
+#[derive(Serialize)]
 pub struct GlRegistry {
+    #[serde(rename = "enums")]
     pub gl_enums: Vec<GlEnum>,
+    #[serde(rename = "enum_blocks")]
+    pub gl_enum_blocks: Vec<GlEnumBlock>,
+    #[serde(rename = "commands")]
     pub gl_commands: Vec<GlCommand>,
+    #[serde(rename = "features")]
     pub gl_features: Vec<GlFeature>,
+    #[serde(rename = "extensions")]
     pub gl_extensions: Vec<GlExtension>,
+    #[serde(rename = "types")]
+    pub gl_types: Vec<GlType>,
+    /// The copyright and license of the registry, found in its leading comment.
+    pub notice: Option<GlNotice>,
+}
+
+/// The copyright notice of a registry, the generated code is derived from it.
+#[derive(Serialize)]
+pub struct GlNotice {
+    /// e.g. "Copyright 2013-2020 The Khronos Group Inc."
+    pub copyright: String,
+    /// The spdx identifier of the license, e.g. "Apache-2.0".
+    pub license: Option<String>,
+}
+
+impl GlNotice {
+    /// Finds the copyright line of a comment and recognizes the license it is followed by.
+    fn parse(comment: &str) -> Option<Self> {
+        let copyright = comment
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("Copyright"))?;
+
+        let license = if let Some(identifier) = comment
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SPDX-License-Identifier:"))
+        {
+            Some(identifier.trim().to_string())
+        } else if comment.contains("Apache License, Version 2.0") {
+            Some("Apache-2.0".to_string())
+        } else if comment.contains("Permission is hereby granted, free of charge") {
+            // The older registries use the Khronos variant of the MIT license.
+            Some("MIT".to_string())
+        } else {
+            None
+        };
+
+        Some(Self {
+            copyright: copyright.to_string(),
+            license,
+        })
+    }
+}
+
+/// A c type declared by the registry, the generator maps them to rust types by hand.
+#[derive(Serialize)]
+pub struct GlType {
+    pub name: String,
+    /// The c declaration of the type, e.g. "typedef unsigned int GLenum;" or "#include <KHR/khrplatform.h>".
+    pub declaration: String,
+    /// The name of the type the declaration depends on.
+    pub requires: Option<String>,
+    pub comment: Option<String>,
 }
 
+#[derive(Clone, Serialize)]
 pub struct GlEnum {
     pub name: String,
     pub value: String,
-    pub bitmask: bool,
+    pub kind: GlEnumKind,
+    #[serde(rename = "groups", serialize_with = "serialize_groups")]
     pub group: Option<String>,
+    /// The type the value is explicitly casted to, e.g. `EGL_CAST(EGLDisplay,0)` in the egl registry.
+    #[serde(rename = "type")]
+    pub gl_type: Option<String>,
+    /// A note of the registry about the enum, e.g. "Guaranteed to mark all attribute groups at once".
+    pub comment: Option<String>,
+    /// The name of the enum this one is an alias of.
+    pub alias: Option<String>,
+    /// The namespace of the block the enum is defined in.
+    pub namespace: Option<String>,
+    /// The vendor owning the block the enum is defined in.
+    pub vendor: Option<String>,
+    /// The only api using this definition of the enum, some enums have different values depending on the api.
+    pub api: Option<Api>,
+}
+
+/// A block of enums, also used by the registry to reserve a range of values to a vendor.
+#[derive(Serialize)]
+pub struct GlEnumBlock {
+    pub namespace: Option<String>,
+    pub group: Option<String>,
+    pub vendor: Option<String>,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+    pub comment: Option<String>,
+}
+
+impl GlEnumBlock {
+    /// Whether the value falls in the range reserved by the block.
+    pub fn contains(&self, value: u64) -> bool {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => (start..=end).contains(&value),
+            (Some(start), None) => start == value,
+            _ => false,
+        }
+    }
+}
+
+/// The kind of value held by an enum, used to pick the type of the generated constant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlEnumKind {
+    /// A 32 bit unsigned value, used by most enums.
+    Unsigned,
+    /// A 64 bit unsigned value, tagged as "ull" by the registry or too big to fit in 32 bits.
+    Unsigned64,
+    /// A negative value.
+    Signed,
+    /// A flag defined in a block tagged as "bitmask" by the registry.
+    Bitmask,
+    /// A string, e.g. "GLX_EXTENSION_NAME".
+    String,
 }
 
+impl GlEnumKind {
+    /// Derives the kind of an enum from its value, its "type" attribute and whether the block it is defined in is a bitmask.
+    fn new(value: &str, enum_type: Option<&str>, bitmask: bool) -> Self {
+        if value.starts_with('"') {
+            return Self::String;
+        }
+
+        match (enum_type, parse_number(value)) {
+            (Some("ull"), _) => Self::Unsigned64,
+            (_, Some(value)) if value > u32::MAX as i128 => Self::Unsigned64,
+            (_, Some(value)) if value < 0 => Self::Signed,
+            (Some("u"), _) => Self::Unsigned,
+            _ if bitmask => Self::Bitmask,
+            _ => Self::Unsigned,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
 pub struct GlCommand {
     pub name: String,
+    #[serde(rename = "params")]
     pub gl_params: Vec<GlParam>,
+    #[serde(serialize_with = "serialize_return_type")]
     pub return_type: String,
+    /// The name of the command this one is an alias of.
+    pub alias: Option<String>,
+}
+
+impl GlCommand {
+    /// The rust type returned by the command, "c_void" if it doesn't return anything.
+    pub fn rust_return_type(&self) -> &str {
+        self.return_type.strip_prefix("->").unwrap_or("c_void")
+    }
 }
 
+#[derive(Clone, Serialize)]
 pub struct GlParam {
+    #[serde(rename = "type")]
     pub gl_type: String,
     pub name: String,
+    /// The number of elements pointed to, e.g. "count*4" or "COMPSIZE(pname)" when it can only be computed at runtime.
+    pub len: Option<String>,
+    /// The group of the enums accepted by the parameter, e.g. "TextureTarget".
+    pub group: Option<String>,
 }
 
+#[derive(Serialize)]
 pub struct GlFeature {
     pub api: Api,
     pub version: f32,
+    #[serde(rename = "remove")]
     pub gl_remove: Vec<GlRequire>,
+    #[serde(rename = "require")]
     pub gl_require: Vec<GlRequire>,
 }
 
+#[derive(Serialize)]
 pub struct GlRequire {
+    #[serde(rename = "profile")]
     pub gl_profile: Option<GlProfile>,
     pub api: Option<Api>,
+    #[serde(rename = "enums")]
     pub gl_enums: Vec<String>,
+    #[serde(rename = "commands")]
     pub gl_commands: Vec<String>,
+    /// Whether the commands can be missing from the context, e.g. the ones added by an include filter.
+    pub optional: bool,
 }
 
+#[derive(Serialize)]
 pub struct GlExtension {
+    pub name: String,
+    /// The apis supporting the extension, e.g. "gl", "glcore" or "gles2".
+    pub supported: Vec<String>,
+    #[serde(rename = "require")]
     pub gl_require: Vec<GlExtensionRequire>,
 }
 
+impl GlExtension {
+    /// Whether the extension can be used with the api and profile.
+    ///
+    /// The gl extensions usable with the core profile are marked as "glcore", the ones marked as "gl" only are compatibility only.
+    pub fn supports(&self, api: Api, profile: GlProfile) -> bool {
+        self.supported.iter().any(|supported| match (api, profile) {
+            (Api::Gl, GlProfile::Core) => supported == "glcore",
+            _ => supported == api.api(),
+        })
+    }
+}
+
+#[derive(Serialize)]
 pub struct GlExtensionRequire {
+    #[serde(rename = "profile")]
+    pub gl_profile: Option<GlProfile>,
+    pub api: Option<Api>,
+    #[serde(rename = "enums")]
     pub gl_enums: Vec<String>,
+    #[serde(rename = "commands")]
     pub gl_commands: Vec<String>,
 }
 
@@ -129,6 +380,8 @@ pub enum ParseError {
     Api(#[from] ApiFromStrError),
     #[error("Failed to parse registry file")]
     Xml(#[from] roxmltree::Error),
+    #[error("Invalid EGL_CAST value {value:?} of {name}")]
+    InvalidCast { name: String, value: String },
 }
 
 impl GlRegistry {
@@ -136,9 +389,18 @@ impl GlRegistry {
         let document = Document::parse(xml)?;
 
         let mut gl_enums = Vec::new();
+        let mut gl_enum_blocks = Vec::new();
         let mut gl_commands = Vec::new();
         let mut gl_features = Vec::new();
         let mut gl_extensions = Vec::new();
+        let mut gl_types = Vec::new();
+
+        // The notice is either in a comment element or in an xml comment, depending on the registry.
+        let notice = document
+            .root_element()
+            .children()
+            .filter(|node| node.is_comment() || node.has_tag_name("comment"))
+            .find_map(|node| node.text().and_then(GlNotice::parse));
 
         // todo!();
 
@@ -149,30 +411,76 @@ impl GlRegistry {
         {
             match node.tag_name().name() {
                 "enums" => {
-                    if let Some(group) = node.attribute("group") {
-                        // TODO: For some reasone nvidia used negative values here, no clue way. I'm fixing this another time.
-                        if group == "TransformFeedbackTokenNV" {
-                            continue;
-                        }
-                    }
-
                     let bitmask = if let Some(t) = node.attribute("type") {
                         t == "bitmask"
                     } else {
                         false
                     };
 
+                    let namespace = node.attribute("namespace").map(|s| s.to_string());
+                    let vendor = node.attribute("vendor").map(|s| s.to_string());
+
+                    // Some registries specify the whole range in the start attribute, e.g. start="0x3060-0x306F".
+                    let (start, end) = match node.attribute("start").map(|s| s.split_once('-')) {
+                        Some(Some((start, end))) => (Some(start), Some(end)),
+                        Some(None) => (node.attribute("start"), node.attribute("end")),
+                        None => (None, None),
+                    };
+
+                    gl_enum_blocks.push(GlEnumBlock {
+                        namespace: namespace.clone(),
+                        group: node.attribute("group").map(|s| s.to_string()),
+                        vendor: vendor.clone(),
+                        start: start
+                            .and_then(parse_number)
+                            .and_then(|start| start.try_into().ok()),
+                        end: end
+                            .and_then(parse_number)
+                            .and_then(|end| end.try_into().ok()),
+                        comment: node.attribute("comment").map(|s| s.to_string()),
+                    });
+
                     for gl_enum in node.children() {
                         if gl_enum.tag_name().name() == "enum" {
                             let name = gl_enum.attribute("name").unwrap().to_string();
-                            let value = gl_enum.attribute("value").unwrap().to_string();
+                            let mut value = gl_enum.attribute("value").unwrap().to_string();
                             let group = gl_enum.attribute("group").map(|s| s.to_string());
+                            let mut gl_type = None;
+
+                            // Egl uses a macro to cast some of its values to a specific type, e.g. "EGL_CAST(EGLint,-1)".
+                            if let Some(cast) = value
+                                .strip_prefix("EGL_CAST(")
+                                .and_then(|cast| cast.strip_suffix(')'))
+                            {
+                                let (cast_type, cast_value) =
+                                    cast.split_once(',').ok_or_else(|| {
+                                        ParseError::InvalidCast {
+                                            name: name.clone(),
+                                            value: value.clone(),
+                                        }
+                                    })?;
+
+                                gl_type = Some(cast_type.trim().to_string());
+                                value = cast_value.trim().to_string();
+                            }
+
+                            let kind = GlEnumKind::new(&value, gl_enum.attribute("type"), bitmask);
+                            let api = gl_enum
+                                .attribute("api")
+                                .map(|api| api.parse())
+                                .transpose()?;
 
                             gl_enums.push(GlEnum {
                                 name,
                                 value,
-                                bitmask,
+                                kind,
                                 group,
+                                gl_type,
+                                comment: gl_enum.attribute("comment").map(|s| s.to_string()),
+                                alias: gl_enum.attribute("alias").map(|s| s.to_string()),
+                                namespace: namespace.clone(),
+                                vendor: vendor.clone(),
+                                api,
                             });
                         }
                     }
@@ -183,6 +491,7 @@ impl GlRegistry {
                             let mut name = None;
                             let mut gl_params = Vec::new();
                             let mut return_type = None;
+                            let mut alias = None;
 
                             for command_attr in gl_command.children() {
                                 match command_attr.tag_name().name() {
@@ -197,25 +506,12 @@ impl GlRegistry {
                                                 .to_string(),
                                         );
 
-                                        let mut gl_type = if let Some(ptype) = command_attr
-                                            .children()
-                                            .find(|node| node.tag_name().name() == "ptype")
-                                        {
-                                            ptype.text().unwrap().to_string()
-                                        } else {
-                                            "".to_string()
-                                        };
+                                        let gl_type = parse_type(command_attr);
 
-                                        if let Some(text) = command_attr.text() {
-                                            if let "const" = text.trim() {
-                                                gl_type = format!("*const {}", gl_type);
-                                            }
-                                        }
-
-                                        if !gl_type.is_empty() {
-                                            return_type = Some(format!("->{}", gl_type));
+                                        if gl_type == "c_void" {
+                                            return_type = Some("".to_string())
                                         } else {
-                                            return_type = Some(gl_type)
+                                            return_type = Some(format!("->{}", gl_type));
                                         }
                                     }
                                     "param" => {
@@ -229,52 +525,30 @@ impl GlRegistry {
 
                                         if KEYWORDS.contains(&name.as_str()) {
                                             name = format!("r#{}", name)
+                                        } else if SHADOWED.contains(&name.as_str()) {
+                                            name = format!("{}_", name)
                                         }
 
-                                        let gl_type = if let Some(node) = command_attr
-                                            .children()
-                                            .find(|node| node.tag_name().name() == "ptype")
-                                        {
-                                            let mut gl_type = match node.text().unwrap().trim() {
-                                                "struct _cl_context" => "*mut _cl_context",
-                                                "struct _cl_event" => "*mut _cl_event",
-                                                gl_type => gl_type,
-                                            }
-                                            .to_string();
-
-                                            if let Some(tail) = node.tail() {
-                                                if tail.trim() == "*" {
-                                                    if let Some(text) = command_attr.text() {
-                                                        if let "const" = text.trim() {
-                                                            gl_type = format!("*const {}", gl_type);
-                                                        }
-                                                    } else {
-                                                        gl_type = format!("*mut {}", gl_type);
-                                                    }
-                                                } else if tail.trim() == "*const*" {
-                                                    gl_type = format!("*const *const {}", gl_type);
-                                                }
-                                            }
+                                        let gl_type = parse_type(command_attr);
+                                        let len = command_attr
+                                            .attribute("len")
+                                            .map(|len| len.to_string());
+                                        let group = command_attr
+                                            .attribute("group")
+                                            .map(|group| group.to_string());
 
-                                            gl_type
-                                        } else {
-                                            match command_attr.text().unwrap().trim() {
-                                                "const void *" => "*const c_void",
-                                                "const void **" | "const void *const*" => {
-                                                    "*const *const c_void"
-                                                }
-                                                "void *" => "*mut c_void",
-                                                "void **" => "*mut *mut c_void",
-                                                text => {
-                                                    panic!("Couldn't find a valid type\n {}", text)
-                                                }
-                                            }
-                                            .to_string()
-                                        };
-
-                                        gl_params.push(GlParam { name, gl_type })
+                                        gl_params.push(GlParam {
+                                            name,
+                                            gl_type,
+                                            len,
+                                            group,
+                                        })
+                                    }
+                                    "alias" => {
+                                        alias = command_attr
+                                            .attribute("name")
+                                            .map(|name| name.to_string())
                                     }
-                                    "alias" => {}
                                     "glx" => {}
                                     "vecequiv" => {}
                                     _ => {
@@ -287,6 +561,7 @@ impl GlRegistry {
                                 name: name.unwrap(),
                                 gl_params,
                                 return_type: return_type.unwrap(),
+                                alias,
                             });
                         }
                     }
@@ -332,6 +607,7 @@ impl GlRegistry {
                                     gl_commands,
                                     gl_profile,
                                     api,
+                                    optional: false,
                                 })
                             }
                             "remove" => {
@@ -367,6 +643,7 @@ impl GlRegistry {
                                     gl_commands,
                                     gl_profile,
                                     api,
+                                    optional: false,
                                 })
                             }
                             name => {
@@ -386,26 +663,95 @@ impl GlRegistry {
                 }
                 "extensions" => {
                     for gl_extension in node.children().filter(|node| node.is_element()) {
-                        if let Some(element) = gl_extension.first_element_child() {
-                            if element.tag_name().name() != "require" {
-                                dbg!(element.tag_name().name());
+                        let name = gl_extension.attribute("name").unwrap().to_string();
+                        let supported = gl_extension
+                            .attribute("supported")
+                            .map(|supported| supported.split('|').map(|s| s.to_string()).collect())
+                            .unwrap_or_default();
+                        let mut gl_require = Vec::new();
+
+                        for gl_extension_require in gl_extension.children() {
+                            match gl_extension_require.tag_name().name() {
+                                "require" => {
+                                    let mut gl_enums = Vec::new();
+                                    let mut gl_commands = Vec::new();
+                                    let api = gl_extension_require
+                                        .attribute("api")
+                                        .map(|api| api.parse())
+                                        .transpose()?;
+                                    let gl_profile = gl_extension_require
+                                        .attribute("profile")
+                                        .map(|profile| profile.parse())
+                                        .transpose()?;
+
+                                    for gl_require in gl_extension_require.children() {
+                                        match gl_require.tag_name().name() {
+                                            "enum" => {
+                                                let gl_enum = gl_require.attribute("name").unwrap();
+                                                gl_enums.push(gl_enum.to_string());
+                                            }
+                                            "command" => {
+                                                let gl_command =
+                                                    gl_require.attribute("name").unwrap();
+                                                gl_commands.push(gl_command.to_string())
+                                            }
+                                            "type" => {}
+                                            name => {
+                                                if !name.is_empty() {
+                                                    panic!("Unknown req {name}")
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    gl_require.push(GlExtensionRequire {
+                                        gl_profile,
+                                        api,
+                                        gl_enums,
+                                        gl_commands,
+                                    })
+                                }
+                                name => {
+                                    if !name.is_empty() {
+                                        panic!("Unknown req {name}")
+                                    }
+                                }
                             }
-                        } else {
-                            dbg!(gl_extension.attribute("name").unwrap());
                         }
 
-                        // if gl_extension.children().count() == 1 {
-                        // for require in gl_extension.first_element_child().unwrap().children() {
-                        //     match require.tag_name().name() {
-                        //         r => {
-                        //             dbg!(r);
-                        //         }
-                        //     }
-                        // }
-                        // }
+                        gl_extensions.push(GlExtension {
+                            name,
+                            supported,
+                            gl_require,
+                        })
+                    }
+                }
+                "types" => {
+                    for gl_type in node.children().filter(|node| node.has_tag_name("type")) {
+                        // Most types have their name in a child node, the rest in an attribute.
+                        let name = match gl_type.attribute("name") {
+                            Some(name) => name.to_string(),
+                            None => gl_type
+                                .children()
+                                .find(|node| node.has_tag_name("name"))
+                                .and_then(|node| node.text())
+                                .ok_or(ParseError::InvalidDocument)?
+                                .to_string(),
+                        };
+
+                        gl_types.push(GlType {
+                            name,
+                            declaration: gl_type
+                                .descendants()
+                                .filter(|node| node.is_text())
+                                .filter_map(|node| node.text())
+                                .collect(),
+                            requires: gl_type.attribute("requires").map(|s| s.to_string()),
+                            comment: gl_type.attribute("comment").map(|s| s.to_string()),
+                        });
                     }
                 }
-                "comment" | "types" => {
+                "comment" => {
                     // Ignore
                 }
                 _invalid => {
@@ -416,17 +762,95 @@ impl GlRegistry {
 
         Ok(Self {
             gl_enums,
+            gl_enum_blocks,
             gl_commands,
             gl_features,
             gl_extensions,
+            gl_types,
+            notice,
         })
     }
 
+    /// Returns the enum blocks reserving the value, the vendor of the block is the one who reserved it.
+    pub fn reserved_by(&self, value: u64) -> impl Iterator<Item = &GlEnumBlock> {
+        self.gl_enum_blocks
+            .iter()
+            .filter(move |gl_enum_block| gl_enum_block.contains(value))
+    }
+
+    /// Returns the highest version of the api defined by the registry.
+    pub fn latest_version(&self, api: Api) -> Option<f32> {
+        self.gl_features
+            .iter()
+            .filter(|gl_feature| gl_feature.api == api)
+            .map(|gl_feature| gl_feature.version)
+            .reduce(f32::max)
+    }
+
+    /// Returns the first version of the api requiring each enum and command.
+    pub fn introduced_in(&self, api: Api, profile: GlProfile) -> HashMap<&str, f32> {
+        let mut introduced_in = HashMap::new();
+
+        let mut gl_features: Vec<&GlFeature> = self
+            .gl_features
+            .iter()
+            .filter(|gl_feature| gl_feature.api == api)
+            .collect();
+        gl_features.sort_by(|a, b| a.version.total_cmp(&b.version));
+
+        for gl_feature in gl_features {
+            for gl_require in &gl_feature.gl_require {
+                if (gl_require.gl_profile.is_none() || gl_require.gl_profile == Some(profile))
+                    && (gl_require.api.is_none() || gl_require.api == Some(api))
+                {
+                    for name in gl_require.gl_enums.iter().chain(&gl_require.gl_commands) {
+                        introduced_in
+                            .entry(name.as_str())
+                            .or_insert(gl_feature.version);
+                    }
+                }
+            }
+        }
+
+        introduced_in
+    }
+
+    /// Returns the extensions requiring each enum and command.
+    pub fn required_by(&self, api: Api, profile: GlProfile) -> HashMap<&str, Vec<&str>> {
+        let mut required_by: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for gl_extension in &self.gl_extensions {
+            if !gl_extension.supports(api, profile) {
+                continue;
+            }
+
+            for gl_require in &gl_extension.gl_require {
+                if (gl_require.gl_profile.is_none() || gl_require.gl_profile == Some(profile))
+                    && (gl_require.api.is_none() || gl_require.api == Some(api))
+                {
+                    for name in gl_require.gl_enums.iter().chain(&gl_require.gl_commands) {
+                        let extensions = required_by.entry(name.as_str()).or_default();
+
+                        if !extensions.contains(&gl_extension.name.as_str()) {
+                            extensions.push(&gl_extension.name);
+                        }
+                    }
+                }
+            }
+        }
+
+        required_by
+    }
+
     // TODO: This code is horribily inefficient, it literaly takes seconds to execute in debug mode. I'll fix it as some point but it works for now.
-    pub fn reduce(&mut self, api: Api, version: f32, profile: GlProfile) {
+    pub fn reduce(&mut self, api: Api, version: f32, profile: GlProfile, extensions: &[String]) {
         self.gl_features
             .retain(|gl_feature| gl_feature.api == api && gl_feature.version <= version);
 
+        self.gl_extensions.retain(|gl_extension| {
+            gl_extension.supports(api, profile) && extensions.contains(&gl_extension.name)
+        });
+
         let mut required_enums: HashSet<&String> = HashSet::new();
         let mut required_commands: HashSet<&String> = HashSet::new();
 
@@ -453,10 +877,273 @@ impl GlRegistry {
             }
         }
 
+        for gl_extension in &self.gl_extensions {
+            for gl_require in &gl_extension.gl_require {
+                if (gl_require.gl_profile.is_none() || gl_require.gl_profile == Some(profile))
+                    && (gl_require.api.is_none() || gl_require.api == Some(api))
+                {
+                    required_enums.extend(&gl_require.gl_enums);
+                    required_commands.extend(&gl_require.gl_commands);
+                }
+            }
+        }
+
         self.gl_commands
             .retain(|gl_command| required_commands.contains(&&gl_command.name));
 
-        self.gl_enums
-            .retain(|gl_enum| required_enums.contains(&&gl_enum.name));
+        self.gl_enums.retain(|gl_enum| {
+            required_enums.contains(&&gl_enum.name)
+                && (gl_enum.api.is_none() || gl_enum.api == Some(api))
+        });
+    }
+
+    /// Keeps only the enums and commands whose name matches the predicate.
+    ///
+    /// They are also removed from the features and extensions, so the generated code doesn't refer to them.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.gl_commands.retain(|gl_command| keep(&gl_command.name));
+        self.gl_enums.retain(|gl_enum| keep(&gl_enum.name));
+
+        for gl_require in self.gl_features.iter_mut().flat_map(|gl_feature| {
+            gl_feature
+                .gl_require
+                .iter_mut()
+                .chain(&mut gl_feature.gl_remove)
+        }) {
+            gl_require.gl_enums.retain(|name| keep(name));
+            gl_require.gl_commands.retain(|name| keep(name));
+        }
+
+        for gl_require in self
+            .gl_extensions
+            .iter_mut()
+            .flat_map(|gl_extension| &mut gl_extension.gl_require)
+        {
+            gl_require.gl_enums.retain(|name| keep(name));
+            gl_require.gl_commands.retain(|name| keep(name));
+        }
+    }
+}
+
+/// Serializes the comma separated groups of an enum as a list.
+fn serialize_groups<S: Serializer>(
+    group: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(group.iter().flat_map(|group| group.split(',')))
+}
+
+/// Serializes the return type of a command without the arrow, see [`GlCommand::rust_return_type`].
+fn serialize_return_type<S: Serializer>(
+    return_type: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(return_type.strip_prefix("->").unwrap_or("c_void"))
+}
+
+/// Parses a decimal or hexadecimal number as found in the registry.
+fn parse_number(number: &str) -> Option<i128> {
+    let number = number.trim();
+
+    match number.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok(),
+        None => number.parse().ok(),
+    }
+}
+
+/// Converts the c declaration of a `proto` or `param` node into the equivalent rust type.
+///
+/// The name of the declaration is ignored, e.g. `const <ptype>GLchar</ptype> *const*<name>strings</name>` becomes `*const *const GLchar`.
+fn parse_type(node: Node) -> String {
+    let declaration = node
+        .children()
+        .filter(|node| node.tag_name().name() != "name")
+        .filter_map(|node| node.text())
+        .collect::<String>()
+        .replace('*', " * ");
+
+    // The words making up the base type, whether the base type is const and whether each pointer level is itself const.
+    let mut base_type = Vec::new();
+    let mut base_const = false;
+    let mut pointers: Vec<bool> = Vec::new();
+
+    for token in declaration.split_whitespace() {
+        match token {
+            "const" => {
+                if let Some(pointer_const) = pointers.last_mut() {
+                    *pointer_const = true;
+                } else {
+                    base_const = true;
+                }
+            }
+            "*" => pointers.push(false),
+            "struct" => {}
+            word => base_type.push(word),
+        }
+    }
+
+    let mut gl_type = match base_type.join(" ").as_str() {
+        "void" => "c_void",
+        "char" => "c_char",
+        "unsigned char" => "c_uchar",
+        "short" => "c_short",
+        "unsigned short" => "c_ushort",
+        "int" => "c_int",
+        "unsigned int" => "c_uint",
+        "long" => "c_long",
+        "unsigned long" => "c_ulong",
+        "float" => "c_float",
+        "double" => "c_double",
+        gl_type => gl_type,
+    }
+    .to_string();
+
+    // Each pointer points to the previous level, so the constness of the previous level determines the kind of pointer.
+    let mut pointee_const = base_const;
+
+    for pointer_const in pointers {
+        gl_type = if pointee_const {
+            format!("*const {gl_type}")
+        } else {
+            format!("*mut {gl_type}")
+        };
+
+        pointee_const = pointer_const;
+    }
+
+    gl_type
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(xml: &str) -> String {
+        parse_type(Document::parse(xml).unwrap().root_element())
+    }
+
+    #[test]
+    fn parse_plain_types() {
+        assert_eq!(
+            parse("<param><ptype>GLenum</ptype> <name>target</name></param>"),
+            "GLenum"
+        );
+        assert_eq!(
+            parse("<proto>unsigned int <name>glGetError</name></proto>"),
+            "c_uint"
+        );
+        assert_eq!(parse("<proto>void <name>glFinish</name></proto>"), "c_void");
+    }
+
+    #[test]
+    fn parse_pointers() {
+        assert_eq!(
+            parse("<param>void *<name>pointer</name></param>"),
+            "*mut c_void"
+        );
+        assert_eq!(
+            parse("<param>const void *<name>data</name></param>"),
+            "*const c_void"
+        );
+        assert_eq!(
+            parse("<param>const <ptype>GLchar</ptype> *<name>name</name></param>"),
+            "*const GLchar"
+        );
+        assert_eq!(
+            parse("<param><ptype>GLsizei</ptype> *<name>length</name></param>"),
+            "*mut GLsizei"
+        );
+    }
+
+    #[test]
+    fn parse_pointers_to_pointers() {
+        assert_eq!(
+            parse("<param>const <ptype>GLchar</ptype> *const*<name>string</name></param>"),
+            "*const *const GLchar"
+        );
+        assert_eq!(
+            parse("<param>void **<name>params</name></param>"),
+            "*mut *mut c_void"
+        );
+        // The pointer itself is const, not the data it points to.
+        assert_eq!(
+            parse("<param>void *const*<name>pointers</name></param>"),
+            "*const *mut c_void"
+        );
+    }
+
+    #[test]
+    fn enum_kinds() {
+        assert_eq!(GlEnumKind::new("0x0DE1", None, false), GlEnumKind::Unsigned);
+        assert_eq!(
+            GlEnumKind::new("0x00004000", None, true),
+            GlEnumKind::Bitmask
+        );
+        assert_eq!(GlEnumKind::new("-1", None, false), GlEnumKind::Signed);
+        assert_eq!(
+            GlEnumKind::new(r#""GLX_VENDOR""#, None, false),
+            GlEnumKind::String
+        );
+    }
+
+    #[test]
+    fn enum_kinds_from_type() {
+        assert_eq!(
+            GlEnumKind::new("0xFFFFFFFFFFFFFFFF", Some("ull"), false),
+            GlEnumKind::Unsigned64
+        );
+        // Small values tagged as "ull" still need 64 bits, e.g. to be compared with a GLuint64.
+        assert_eq!(
+            GlEnumKind::new("0x1", Some("ull"), false),
+            GlEnumKind::Unsigned64
+        );
+        // "u" values aren't bitmasks even in a bitmask block, e.g. GL_INVALID_INDEX.
+        assert_eq!(
+            GlEnumKind::new("0xFFFFFFFF", Some("u"), true),
+            GlEnumKind::Unsigned
+        );
+    }
+
+    #[test]
+    fn enum_kinds_from_value() {
+        assert_eq!(
+            GlEnumKind::new("0xFFFFFFFF", None, false),
+            GlEnumKind::Unsigned
+        );
+        assert_eq!(
+            GlEnumKind::new("0x100000000", None, false),
+            GlEnumKind::Unsigned64
+        );
+        assert_eq!(
+            GlEnumKind::new("0xFFFFFFFFFFFFFFFF", None, true),
+            GlEnumKind::Unsigned64
+        );
+        assert_eq!(GlEnumKind::new("-1", None, true), GlEnumKind::Signed);
+    }
+
+    #[test]
+    fn parse_casts() {
+        let enums = |value: &str| {
+            GlRegistry::parse(&format!(
+                r#"<registry><enums namespace="EGL"><enum value="{value}" name="EGL_NO_DISPLAY"/></enums></registry>"#
+            ))
+        };
+
+        let registry = enums("EGL_CAST(EGLDisplay,0)").unwrap();
+        assert_eq!(registry.gl_enums[0].gl_type.as_deref(), Some("EGLDisplay"));
+        assert_eq!(registry.gl_enums[0].value, "0");
+
+        assert!(matches!(
+            enums("EGL_CAST(EGLDisplay)"),
+            Err(ParseError::InvalidCast { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_structs() {
+        assert_eq!(
+            parse("<param>struct <ptype>_cl_context</ptype> *<name>context</name></param>"),
+            "*mut _cl_context"
+        );
     }
 }
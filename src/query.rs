@@ -0,0 +1,237 @@
+use itertools::Itertools;
+use std::fmt;
+
+use crate::parser::{Api, GlCommand, GlEnum, GlProfile, GlRegistry};
+
+/// The feature or extension requiring or removing an enum or command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Origin<'a> {
+    Feature {
+        api: Api,
+        version: f32,
+        profile: Option<GlProfile>,
+    },
+    Extension(&'a str),
+}
+
+impl fmt::Display for Origin<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Feature {
+                api,
+                version,
+                profile: Some(profile),
+            } => write!(f, "{} {version:.1} ({profile:?} profile)", api.api()),
+            Origin::Feature { api, version, .. } => write!(f, "{} {version:.1}", api.api()),
+            Origin::Extension(name) => f.write_str(name),
+        }
+    }
+}
+
+/// The definition of an enum or command.
+pub enum Item<'a> {
+    Command(&'a GlCommand),
+    Enum(&'a GlEnum),
+}
+
+/// Everything the registry knows about an enum or command.
+pub struct QueryResult<'a> {
+    pub item: Item<'a>,
+    pub required_by: Vec<Origin<'a>>,
+    pub removed_by: Vec<Origin<'a>>,
+    /// The enums or commands that are aliases of this one.
+    pub aliased_by: Vec<&'a str>,
+}
+
+impl GlRegistry {
+    /// Looks up an enum or command by name, ignoring the case and the api prefix if there is no exact match.
+    ///
+    /// Returns `None` if there is no such enum or command, see [`GlRegistry::suggest`] to find similar names.
+    pub fn query(&self, name: &str) -> Option<QueryResult<'_>> {
+        let name = self.resolve(name)?;
+
+        let item = match self
+            .gl_commands
+            .iter()
+            .find(|gl_command| gl_command.name == name)
+        {
+            Some(gl_command) => Item::Command(gl_command),
+            None => Item::Enum(self.gl_enums.iter().find(|gl_enum| gl_enum.name == name)?),
+        };
+
+        let mut required_by = Vec::new();
+        let mut removed_by = Vec::new();
+
+        for gl_feature in &self.gl_features {
+            for (gl_requires, origins) in [
+                (&gl_feature.gl_require, &mut required_by),
+                (&gl_feature.gl_remove, &mut removed_by),
+            ] {
+                for gl_require in gl_requires {
+                    if gl_require
+                        .gl_enums
+                        .iter()
+                        .chain(&gl_require.gl_commands)
+                        .any(|required| required == name)
+                    {
+                        origins.push(Origin::Feature {
+                            api: gl_require.api.unwrap_or(gl_feature.api),
+                            version: gl_feature.version,
+                            profile: gl_require.gl_profile,
+                        });
+                    }
+                }
+            }
+        }
+
+        for gl_extension in &self.gl_extensions {
+            let required = gl_extension.gl_require.iter().any(|gl_require| {
+                gl_require
+                    .gl_enums
+                    .iter()
+                    .chain(&gl_require.gl_commands)
+                    .any(|required| required == name)
+            });
+
+            if required {
+                required_by.push(Origin::Extension(&gl_extension.name));
+            }
+        }
+
+        let aliased_by = self
+            .gl_commands
+            .iter()
+            .filter(|gl_command| gl_command.alias.as_deref() == Some(name))
+            .map(|gl_command| gl_command.name.as_str())
+            .chain(
+                self.gl_enums
+                    .iter()
+                    .filter(|gl_enum| gl_enum.alias.as_deref() == Some(name))
+                    .map(|gl_enum| gl_enum.name.as_str()),
+            )
+            .unique()
+            .collect();
+
+        Some(QueryResult {
+            item,
+            required_by: required_by.into_iter().dedup().collect(),
+            removed_by: removed_by.into_iter().dedup().collect(),
+            aliased_by,
+        })
+    }
+
+    /// Returns the names of the enums and commands closest to a misspelled one, the closest first.
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<&str> {
+        let name = name.to_lowercase();
+
+        self.names()
+            .map(|candidate| {
+                (
+                    strsim::jaro_winkler(&name, &candidate.to_lowercase()),
+                    candidate,
+                )
+            })
+            .filter(|(similarity, _)| *similarity > 0.8)
+            .sorted_by(|(a, _), (b, _)| b.total_cmp(a))
+            .map(|(_, candidate)| candidate)
+            .unique()
+            .take(limit)
+            .collect()
+    }
+
+    /// Returns the names of the extensions closest to a misspelled one, the closest first.
+    pub fn suggest_extension(&self, name: &str, limit: usize) -> Vec<&str> {
+        let name = name.to_lowercase();
+
+        self.gl_extensions
+            .iter()
+            .map(|gl_extension| {
+                (
+                    strsim::jaro_winkler(&name, &gl_extension.name.to_lowercase()),
+                    gl_extension.name.as_str(),
+                )
+            })
+            .filter(|(similarity, _)| *similarity > 0.8)
+            .sorted_by(|(a, _), (b, _)| b.total_cmp(a))
+            .map(|(_, candidate)| candidate)
+            .take(limit)
+            .collect()
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.gl_commands
+            .iter()
+            .map(|gl_command| gl_command.name.as_str())
+            .chain(self.gl_enums.iter().map(|gl_enum| gl_enum.name.as_str()))
+    }
+
+    /// Finds the exact name of an enum or command, e.g. "texstorage2d" becomes "glTexStorage2D".
+    fn resolve(&self, name: &str) -> Option<&str> {
+        if let Some(exact) = self.names().find(|candidate| *candidate == name) {
+            return Some(exact);
+        }
+
+        let name = name.to_lowercase();
+
+        self.names().find(|candidate| {
+            let candidate = candidate.to_lowercase();
+
+            // The prefixes of all the registries, lowercased: "gl"/"GL_", "egl"/"EGL_", "glX"/"GLX_" and "wgl"/"WGL_".
+            candidate == name
+                || ["gl", "gl_", "egl", "egl_", "glx", "glx_", "wgl", "wgl_"]
+                    .iter()
+                    .any(|prefix| candidate.strip_prefix(prefix) == Some(name.as_str()))
+        })
+    }
+}
+
+impl fmt::Display for QueryResult<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let origins = |origins: &[Origin]| match origins {
+            [] => "-".to_string(),
+            origins => origins.iter().join(", "),
+        };
+
+        let alias = match &self.item {
+            Item::Command(gl_command) => {
+                let parameters = gl_command
+                    .gl_params
+                    .iter()
+                    .format_with(", ", |gl_param, f| match &gl_param.group {
+                        Some(group) => f(&format_args!(
+                            "{}: {} [{group}]",
+                            gl_param.name, gl_param.gl_type
+                        )),
+                        None => f(&format_args!("{}: {}", gl_param.name, gl_param.gl_type)),
+                    });
+                let return_type = gl_command.return_type.replace("->", " -> ");
+
+                writeln!(f, "{}({parameters}){return_type}", gl_command.name)?;
+
+                &gl_command.alias
+            }
+            Item::Enum(gl_enum) => {
+                writeln!(f, "{} = {}", gl_enum.name, gl_enum.value)?;
+                writeln!(
+                    f,
+                    "  groups: {}",
+                    gl_enum.group.as_deref().unwrap_or("-").replace(',', ", ")
+                )?;
+
+                &gl_enum.alias
+            }
+        };
+
+        writeln!(f, "  introduced by: {}", origins(&self.required_by))?;
+        writeln!(f, "  removed by: {}", origins(&self.removed_by))?;
+        writeln!(f, "  alias of: {}", alias.as_deref().unwrap_or("-"))?;
+        write!(
+            f,
+            "  aliased by: {}",
+            match self.aliased_by.as_slice() {
+                [] => "-".to_string(),
+                aliased_by => aliased_by.join(", "),
+            }
+        )
+    }
+}
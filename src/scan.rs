@@ -0,0 +1,341 @@
+use std::{collections::BTreeSet, fs, io, path::Path};
+
+use crate::parser::{Api, GlRegistry};
+
+/// The identifiers found in rust sources, used to only generate the enums and commands actually called.
+#[derive(Default)]
+pub struct Usage {
+    /// The identifiers followed by parentheses, e.g. "Clear" in `gl.Clear(GL_COLOR_BUFFER_BIT)`.
+    pub calls: BTreeSet<String>,
+    /// Every other identifier, e.g. "GL_COLOR_BUFFER_BIT".
+    pub identifiers: BTreeSet<String>,
+}
+
+/// The enums and commands referenced by the sources, resolved against the registry.
+pub struct UsageReport {
+    pub commands: BTreeSet<String>,
+    pub enums: BTreeSet<String>,
+    /// The referenced enums and commands missing from the registry once reduced, e.g. a command introduced by a newer version.
+    pub missing: BTreeSet<String>,
+}
+
+impl Usage {
+    /// Scans a rust source file, or all the ones in a directory and its subdirectories.
+    ///
+    /// "target" directories and hidden ones are skipped.
+    pub fn scan_path(path: &Path) -> io::Result<Self> {
+        let mut usage = Self::default();
+        let mut paths = vec![path.to_path_buf()];
+
+        while let Some(path) = paths.pop() {
+            if path.is_dir() {
+                for entry in fs::read_dir(&path)? {
+                    let path = entry?.path();
+
+                    let skipped = path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name == "target" || name.starts_with('.'));
+
+                    if !skipped {
+                        paths.push(path);
+                    }
+                }
+            } else if path.extension().is_some_and(|extension| extension == "rs") {
+                usage.scan(&fs::read_to_string(&path)?);
+            }
+        }
+
+        Ok(usage)
+    }
+
+    /// Collects the identifiers of some rust source code, ignoring comments and literals.
+    pub fn scan(&mut self, source: &str) {
+        let chars: Vec<char> = source.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match (chars[i], chars.get(i + 1).copied()) {
+                // Line comments, doc comments included.
+                ('/', Some('/')) => {
+                    while i < chars.len() && chars[i] != '\n' {
+                        i += 1;
+                    }
+                }
+                // Block comments, which can be nested.
+                ('/', Some('*')) => {
+                    let mut depth = 0;
+
+                    while i < chars.len() {
+                        match (chars[i], chars.get(i + 1).copied()) {
+                            ('/', Some('*')) => {
+                                depth += 1;
+                                i += 2;
+                            }
+                            ('*', Some('/')) => {
+                                depth -= 1;
+                                i += 2;
+
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            _ => i += 1,
+                        }
+                    }
+                }
+                ('"', _) => i = skip_string(&chars, i + 1),
+                // Byte and c strings, e.g. b"..." and c"...", as opposed to identifiers starting with these letters.
+                ('b' | 'c', Some('"')) => i = skip_string(&chars, i + 2),
+                // Raw byte and c strings, e.g. br#"..."#, left to the raw strings below.
+                ('b' | 'c', Some('r')) if matches!(chars.get(i + 2), Some('#' | '"')) => i += 1,
+                // Raw strings, e.g. r#"..."#, as opposed to raw identifiers like r#type.
+                ('r', Some('#' | '"')) => {
+                    let hashes = chars[i + 1..].iter().take_while(|c| **c == '#').count();
+                    let start = i + 1 + hashes;
+
+                    if chars.get(start) != Some(&'"') {
+                        i += 1;
+                        continue;
+                    }
+
+                    let closing: Vec<char> = std::iter::once('"')
+                        .chain(std::iter::repeat_n('#', hashes))
+                        .collect();
+
+                    i = start + 1;
+
+                    while i < chars.len() && !chars[i..].starts_with(&closing) {
+                        i += 1;
+                    }
+
+                    i += closing.len();
+                }
+                // Character literals, as opposed to lifetimes, e.g. 'a' and '\n' but not 'a.
+                ('\'', Some('\\')) => {
+                    i += 2;
+
+                    while i < chars.len() && chars[i] != '\'' {
+                        i += 1;
+                    }
+
+                    i += 1;
+                }
+                ('\'', Some(_)) if chars.get(i + 2) == Some(&'\'') => i += 3,
+                (c, _) if c.is_alphabetic() || c == '_' => {
+                    let start = i;
+
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                        i += 1;
+                    }
+
+                    let identifier: String = chars[start..i].iter().collect();
+
+                    let called = chars[i..]
+                        .iter()
+                        .find(|c| !c.is_whitespace())
+                        .is_some_and(|c| *c == '(');
+
+                    if called {
+                        self.calls.insert(identifier);
+                    } else {
+                        self.identifiers.insert(identifier);
+                    }
+                }
+                // Numbers, so suffixes like "u32" in 1u32 aren't mistaken for identifiers.
+                (c, _) if c.is_ascii_digit() => {
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                        i += 1;
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+    }
+
+    /// Resolves the calls to commands, with or without their api prefix, and the enums against the registry.
+    ///
+    /// The registry must contain all the versions of the api, so the commands and enums not provided once it is reduced can be reported.
+    pub fn resolve(&self, registry: &GlRegistry, api: Api) -> BTreeSet<String> {
+        let commands = registry
+            .gl_commands
+            .iter()
+            .filter(|gl_command| {
+                let name = gl_command
+                    .name
+                    .strip_prefix(api.prefix())
+                    .unwrap_or(&gl_command.name);
+
+                self.calls.contains(name) || self.calls.contains(&gl_command.name)
+            })
+            .map(|gl_command| gl_command.name.clone());
+
+        let enums = registry
+            .gl_enums
+            .iter()
+            .filter(|gl_enum| self.identifiers.contains(&gl_enum.name))
+            .map(|gl_enum| gl_enum.name.clone());
+
+        commands.chain(enums).collect()
+    }
+}
+
+impl UsageReport {
+    /// Reports which of the referenced enums and commands are part of the reduced registry.
+    pub fn new(referenced: &BTreeSet<String>, registry: &GlRegistry) -> Self {
+        let commands: BTreeSet<String> = registry
+            .gl_commands
+            .iter()
+            .filter(|gl_command| referenced.contains(&gl_command.name))
+            .map(|gl_command| gl_command.name.clone())
+            .collect();
+
+        let enums: BTreeSet<String> = registry
+            .gl_enums
+            .iter()
+            .filter(|gl_enum| referenced.contains(&gl_enum.name))
+            .map(|gl_enum| gl_enum.name.clone())
+            .collect();
+
+        let missing = referenced
+            .iter()
+            .filter(|name| !commands.contains(*name) && !enums.contains(*name))
+            .cloned()
+            .collect();
+
+        Self {
+            commands,
+            enums,
+            missing,
+        }
+    }
+}
+
+/// Returns the index following the end of a string literal, escaped quotes included.
+fn skip_string(chars: &[char], mut i: usize) -> usize {
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> Usage {
+        let mut usage = Usage::default();
+        usage.scan(source);
+        usage
+    }
+
+    #[test]
+    fn calls_and_identifiers() {
+        let usage = scan("gl.Clear (GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT);");
+
+        assert!(usage.calls.contains("Clear"));
+        assert!(usage.identifiers.contains("GL_COLOR_BUFFER_BIT"));
+        assert!(usage.identifiers.contains("GL_DEPTH_BUFFER_BIT"));
+    }
+
+    #[test]
+    fn raw_strings() {
+        let usage = scan(
+            r###"let s = r#"gl.Clear(GL_BLEND) "quoted""#; let t = r"GL_CULL_FACE"; r#type(GL_DEPTH_TEST);"###,
+        );
+
+        assert!(!usage.calls.contains("Clear"));
+        assert!(!usage.identifiers.contains("GL_BLEND"));
+        assert!(!usage.identifiers.contains("GL_CULL_FACE"));
+        assert!(usage.calls.contains("type"));
+        assert!(usage.identifiers.contains("GL_DEPTH_TEST"));
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        let usage =
+            scan("/* outer /* gl.Clear(GL_BLEND) */ GL_CULL_FACE */ gl.Enable(GL_DEPTH_TEST);");
+
+        assert!(!usage.calls.contains("Clear"));
+        assert!(!usage.identifiers.contains("GL_BLEND"));
+        assert!(!usage.identifiers.contains("GL_CULL_FACE"));
+        assert!(usage.calls.contains("Enable"));
+        assert!(usage.identifiers.contains("GL_DEPTH_TEST"));
+    }
+
+    #[test]
+    fn char_literals_and_lifetimes() {
+        let usage =
+            scan(r"fn f<'a>(gl: &'a Gl) { let c = ['X', '\'', '(']; gl.Enable(GL_BLEND); }");
+
+        // Lifetimes are identifiers, they don't swallow what follows them.
+        assert!(usage.identifiers.contains("a"));
+        assert!(usage.identifiers.contains("Gl"));
+        assert!(!usage.identifiers.contains("X"));
+        assert!(usage.calls.contains("Enable"));
+        assert!(usage.identifiers.contains("GL_BLEND"));
+    }
+
+    #[test]
+    fn byte_strings() {
+        let usage = scan(
+            r###"load(b"glClear(GL_BLEND)\0", b'"', br#"GL_CULL_FACE "quoted""#, cr"GL_DITHER"); GL_DEPTH_TEST"###,
+        );
+
+        assert!(usage.calls.contains("load"));
+        assert!(!usage.calls.contains("glClear"));
+        assert!(!usage.identifiers.contains("GL_BLEND"));
+        assert!(!usage.identifiers.contains("GL_CULL_FACE"));
+        assert!(!usage.identifiers.contains("quoted"));
+        assert!(!usage.identifiers.contains("GL_DITHER"));
+        assert!(usage.identifiers.contains("GL_DEPTH_TEST"));
+    }
+
+    #[test]
+    fn report_missing() {
+        let registry = GlRegistry::parse(
+            r#"<registry>
+                <enums namespace="GL" group="AttribMask" type="bitmask">
+                    <enum value="0x00004000" name="GL_COLOR_BUFFER_BIT"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto>void <name>glClear</name></proto>
+                        <param><ptype>GLbitfield</ptype> <name>mask</name></param>
+                    </command>
+                </commands>
+            </registry>"#,
+        )
+        .unwrap();
+
+        let referenced = [
+            "glClear",
+            "GL_COLOR_BUFFER_BIT",
+            "glDispatchCompute",
+            "GL_COMPUTE_SHADER",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let report = UsageReport::new(&referenced, &registry);
+
+        assert_eq!(report.commands, BTreeSet::from(["glClear".to_string()]));
+        assert_eq!(
+            report.enums,
+            BTreeSet::from(["GL_COLOR_BUFFER_BIT".to_string()])
+        );
+        assert_eq!(
+            report.missing,
+            BTreeSet::from([
+                "GL_COMPUTE_SHADER".to_string(),
+                "glDispatchCompute".to_string()
+            ])
+        );
+    }
+}
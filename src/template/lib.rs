@@ -1,4 +1,4 @@
 #![cfg_attr(not(feature = "std"), no_std, feature(core_c_str), feature(core_ffi_c))]
 
-mod gl;
-pub use gl::*;
+{cfg}mod {module};
+{cfg}pub use {module}::*;